@@ -0,0 +1,186 @@
+// Copyright 2019 Matthieu Felix
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Allocation of a budget between convex cost functions.
+
+use crate::{bogus_compare, segment_slope, CoordFloat, Error, PiecewiseLinearFunction};
+
+/// An optimal solution of the problem solved by `allocate()`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Allocation<T: CoordFloat> {
+    /// The amount allocated to each function, in the order in which they were passed.
+    pub amounts: Vec<T>,
+    /// The marginal price of the budget, that is, the increase of the total cost per unit of
+    /// additional budget. This is the Lagrange multiplier of the budget constraint.
+    pub price: T,
+    /// The total cost of the allocation.
+    pub cost: T,
+}
+
+/// Minimizes Σ fi(xi) subject to Σ xi = `budget`, where the fi are convex functions.
+///
+/// Every function starts at the start of its domain, and the rest of the budget is allocated
+/// greedily to the segments of all functions in order of increasing slope; convexity makes this
+/// optimal. The total cost is the value at `budget` of the infimal convolution of the functions
+/// (see `infimal_convolution()`).
+///
+/// The marginal price is the slope of the last segment which received part of the budget, or the
+/// smallest slope of all functions if `budget` is the sum of the starts of their domains. When
+/// `budget` falls exactly at the end of a segment, any price between this slope and the next one
+/// is also a valid multiplier.
+///
+/// Functions may extend to +inf, in which case the unbounded budget goes to the ray with the
+/// smallest slope.
+///
+/// Returns `Error::NotConvex` if a function is not convex, `Error::UnboundedDomain` if the domain
+/// of a function extends to -inf, and `Error::Infeasible` if there are no functions or if
+/// `budget` is outside of the range that the domains of the functions can add up to.
+///
+/// ## Example
+///
+/// ```
+/// use piecewise_linear::{allocate, PiecewiseLinearFunction};
+/// use std::convert::TryFrom;
+/// let f = PiecewiseLinearFunction::try_from(vec![(0., 0.), (10., 20.), (20., 60.)]).unwrap();
+/// let g = PiecewiseLinearFunction::try_from(vec![(5., 10.), (15., 40.)]).unwrap();
+/// let allocation = allocate(&[f, g], 20.).unwrap();
+/// assert_eq!(allocation.amounts, vec![10., 10.]);
+/// assert_eq!(allocation.price, 3.);
+/// assert_eq!(allocation.cost, 20. + 25.);
+/// ```
+///
+/// ## Complexity
+///
+/// The complexity of this method is _O(n log(n))_, where _n_ is the total number of points in the
+/// functions.
+pub fn allocate<T: CoordFloat>(
+    funcs: &[PiecewiseLinearFunction<T>],
+    budget: T,
+) -> Result<Allocation<T>, Error> {
+    if funcs.is_empty() || !budget.is_finite() {
+        return Err(Error::Infeasible);
+    }
+    if !funcs.iter().all(|f| f.is_convex()) {
+        return Err(Error::NotConvex);
+    }
+    if funcs.iter().any(|f| f.domain().0.is_infinite()) {
+        return Err(Error::UnboundedDomain);
+    }
+
+    let mut amounts: Vec<T> = funcs.iter().map(|f| f.domain().0).collect();
+    let mut remaining = budget - amounts.iter().fold(T::zero(), |a, &b| a + b);
+    if remaining < T::zero() {
+        return Err(Error::Infeasible);
+    }
+
+    // Each piece is the index of its function, its slope, and the position of its end.
+    let mut pieces: Vec<(usize, T, T)> = funcs
+        .iter()
+        .enumerate()
+        .flat_map(|(i, f)| {
+            f.segments_iter()
+                .map(move |s| (i, segment_slope(&s), s.end.x))
+        })
+        .collect();
+    // The sort is stable: segments of a function stay in order when they have the same slope.
+    pieces.sort_by(|a, b| bogus_compare(&a.1, &b.1));
+
+    let mut price = pieces[0].1;
+    for &(i, slope, end) in &pieces {
+        if remaining <= T::zero() {
+            break;
+        }
+        let width = end - amounts[i];
+        price = slope;
+        if width <= remaining {
+            amounts[i] = end;
+            remaining = remaining - width;
+        } else {
+            amounts[i] = amounts[i] + remaining;
+            remaining = T::zero();
+        }
+    }
+    if remaining > T::zero() {
+        return Err(Error::Infeasible);
+    }
+
+    let cost = funcs
+        .iter()
+        .zip(&amounts)
+        .map(|(f, &x)| f.y_at_x(x).unwrap())
+        .fold(T::zero(), |a, b| a + b);
+    Ok(Allocation {
+        amounts,
+        price,
+        cost,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use super::*;
+    use crate::infimal_convolution;
+
+    #[test]
+    fn test_allocate() {
+        let funcs = vec![
+            PiecewiseLinearFunction::try_from(vec![(0., 0.), (10., 20.), (20., 60.)]).unwrap(),
+            PiecewiseLinearFunction::try_from(vec![(5., 10.), (15., 40.)]).unwrap(),
+            PiecewiseLinearFunction::try_from(vec![(0., 0.), (4., 4.), (8., 16.), (12., 40.)])
+                .unwrap(),
+        ];
+        let total = infimal_convolution(&funcs).unwrap();
+        for i in 6..=47 {
+            let budget = f64::from(i);
+            let allocation = allocate(&funcs, budget).unwrap();
+            let sum: f64 = allocation.amounts.iter().sum();
+            assert!((sum - budget).abs() < 1e-12);
+            assert!((allocation.cost - total.y_at_x(budget).unwrap()).abs() < 1e-9);
+            let left_slope = total.segment_at_x(budget - 0.5).unwrap().slope();
+            assert_eq!(allocation.price, left_slope, "at {}", budget);
+        }
+
+        let start = allocate(&funcs, 5.).unwrap();
+        assert_eq!(start.amounts, vec![0., 5., 0.]);
+        assert_eq!(start.price, 1.);
+        assert_eq!(allocate(&funcs, 4.), Err(Error::Infeasible));
+        assert_eq!(allocate(&funcs, 48.), Err(Error::Infeasible));
+        assert_eq!(allocate::<f64>(&[], 0.), Err(Error::Infeasible));
+    }
+
+    #[test]
+    fn test_allocate_errors_and_rays() {
+        let concave =
+            PiecewiseLinearFunction::try_from(vec![(0., 0.), (1., 2.), (2., 3.)]).unwrap();
+        assert_eq!(allocate(&[concave], 1.), Err(Error::NotConvex));
+
+        let unlimited = PiecewiseLinearFunction::with_edge_slopes(
+            vec![(0., 0.).into(), (1., 1.).into()],
+            None,
+            Some(5.),
+        )
+        .unwrap();
+        let f = PiecewiseLinearFunction::try_from(vec![(0., 0.), (2., 4.), (3., 10.)]).unwrap();
+        let allocation = allocate(&[unlimited.clone(), f.clone()], 10.).unwrap();
+        assert_eq!(allocation.amounts, vec![8., 2.]);
+        assert_eq!(allocation.price, 5.);
+        assert_eq!(allocation.cost, 1. + 35. + 4.);
+        assert_eq!(
+            allocate(&[unlimited.mirror(), f], 1.),
+            Err(Error::UnboundedDomain)
+        );
+    }
+}
@@ -0,0 +1,655 @@
+// Copyright 2019 Matthieu Felix
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Compact binary encoding of functions over `f32` or `f64`.
+//!
+//! An encoded function starts with a header made of the magic bytes `PLF`, the format version,
+//! the size in bytes of the numeric type, and the encoding. The number of points follows as an
+//! unsigned LEB128 varint, and then the points, depending on the encoding:
+//!
+//! - `Raw`: each point as `x` then `y`, in little endian. This layout can be read in place with a
+//!   `BinaryView`, for instance straight from a memory-mapped file.
+//! - `Delta`: the first `x` in little endian, then for each following `x` the change in the
+//!   difference between its bit pattern and the previous one, as zigzag varints. Regularly spaced
+//!   values take a single byte each. The `y` values follow in little endian.
+//! - `DeltaXor`: the same `x` values, with the `y` values compressed by XOR with the previous
+//!   value, as in Facebook's Gorilla time series database. This works best for slowly varying
+//!   values.
+//!
+//! All encodings operate on bit patterns, so decoding gives back exactly the encoded values.
+//!
+//! ## Example
+//!
+//! ```
+//! use piecewise_linear::binary::{BinaryEncoding, BinaryView};
+//! use piecewise_linear::PiecewiseLinearFunction;
+//! use std::convert::TryFrom;
+//! let f = PiecewiseLinearFunction::try_from(vec![(0., 1.), (1., 1.), (2., 3.)]).unwrap();
+//! let compressed = f.to_bytes(BinaryEncoding::DeltaXor);
+//! assert_eq!(PiecewiseLinearFunction::from_bytes(&compressed), Ok(f.clone()));
+//!
+//! let raw = f.to_bytes(BinaryEncoding::Raw);
+//! let view = BinaryView::<f64>::new(&raw).unwrap();
+//! assert_eq!(view.y_at_x(1.5), Some(2.));
+//! ```
+
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::marker::PhantomData;
+
+use crate::{y_at_x, Coord, CoordFloat, Error, Line, PiecewiseLinearFunction};
+
+const MAGIC: &[u8] = b"PLF";
+const VERSION: u8 = 1;
+/// Length of the magic bytes, version, type size and encoding.
+const HEADER_LENGTH: usize = 6;
+
+/// Numeric types which can be encoded in the binary format.
+pub trait BinaryFloat: CoordFloat {
+    /// The size of the type in bytes.
+    const BYTES: usize;
+
+    /// Returns the bit pattern of the value, in the low bits.
+    fn to_bits_u64(self) -> u64;
+
+    /// Returns the value with the bit pattern in the low bits of `bits`.
+    fn from_bits_u64(bits: u64) -> Self;
+}
+
+impl BinaryFloat for f32 {
+    const BYTES: usize = 4;
+
+    fn to_bits_u64(self) -> u64 {
+        u64::from(self.to_bits())
+    }
+
+    fn from_bits_u64(bits: u64) -> Self {
+        f32::from_bits(bits as u32)
+    }
+}
+
+impl BinaryFloat for f64 {
+    const BYTES: usize = 8;
+
+    fn to_bits_u64(self) -> u64 {
+        self.to_bits()
+    }
+
+    fn from_bits_u64(bits: u64) -> Self {
+        f64::from_bits(bits)
+    }
+}
+
+/// How the points of a function are stored by `to_bytes()` on `PiecewiseLinearFunction`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BinaryEncoding {
+    /// Fixed-size points, which can be read in place by a `BinaryView`.
+    Raw,
+    /// Delta-encoded `x` values and raw `y` values.
+    Delta,
+    /// Delta-encoded `x` values and XOR-compressed `y` values.
+    DeltaXor,
+}
+
+impl BinaryEncoding {
+    fn tag(self) -> u8 {
+        match self {
+            BinaryEncoding::Raw => 0,
+            BinaryEncoding::Delta => 1,
+            BinaryEncoding::DeltaXor => 2,
+        }
+    }
+
+    fn from_tag(tag: u8) -> Option<Self> {
+        match tag {
+            0 => Some(BinaryEncoding::Raw),
+            1 => Some(BinaryEncoding::Delta),
+            2 => Some(BinaryEncoding::DeltaXor),
+            _ => None,
+        }
+    }
+}
+
+impl<T: BinaryFloat> PiecewiseLinearFunction<T> {
+    /// Encodes this function in the binary format, with the given encoding.
+    pub fn to_bytes(&self, encoding: BinaryEncoding) -> Vec<u8> {
+        let mut bytes =
+            Vec::with_capacity(HEADER_LENGTH + 10 + self.coordinates.len() * 2 * T::BYTES);
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&[VERSION, T::BYTES as u8, encoding.tag()]);
+        write_varint(&mut bytes, self.coordinates.len() as u64);
+        let x_bits = self.coordinates.iter().map(|c| c.x.to_bits_u64());
+        let y_bits = self.coordinates.iter().map(|c| c.y.to_bits_u64());
+
+        if encoding == BinaryEncoding::Raw {
+            for (x, y) in x_bits.zip(y_bits) {
+                write_fixed::<T>(&mut bytes, x);
+                write_fixed::<T>(&mut bytes, y);
+            }
+            return bytes;
+        }
+
+        let (mut previous, mut previous_delta) = (None, 0u64);
+        for x in x_bits {
+            match previous {
+                None => write_fixed::<T>(&mut bytes, x),
+                Some(p) => {
+                    let delta = x.wrapping_sub(p);
+                    write_varint(
+                        &mut bytes,
+                        zigzag(delta.wrapping_sub(previous_delta) as i64),
+                    );
+                    previous_delta = delta;
+                }
+            }
+            previous = Some(x);
+        }
+        if encoding == BinaryEncoding::Delta {
+            y_bits.for_each(|y| write_fixed::<T>(&mut bytes, y));
+        } else {
+            write_xor(&mut bytes, y_bits, T::BYTES as u32 * 8);
+        }
+        bytes
+    }
+
+    /// Decodes a function encoded with `to_bytes()`.
+    ///
+    /// Returns `Error::Decode` if the input is not valid or was encoded with another numeric type,
+    /// and the errors of `try_new()` if the decoded points are not a valid function.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let (encoding, count, mut data) = read_header::<T>(bytes)?;
+        let mut coordinates = Vec::with_capacity(count.min(data.len()));
+
+        if encoding == BinaryEncoding::Raw {
+            let view = BinaryView::<T>::from_points(data, count)?;
+            coordinates.extend((0..count).map(|i| view.coordinate(i)));
+        } else {
+            let mut x_bits: Vec<u64> = Vec::with_capacity(count.min(data.len()));
+            let mut delta = 0u64;
+            for i in 0..count {
+                let x = if i == 0 {
+                    read_fixed::<T>(&mut data)?
+                } else {
+                    delta = delta.wrapping_add(unzigzag(read_varint(&mut data)?) as u64);
+                    x_bits[i - 1].wrapping_add(delta)
+                };
+                x_bits.push(x);
+            }
+            let y_bits = if encoding == BinaryEncoding::Delta {
+                (0..count)
+                    .map(|_| read_fixed::<T>(&mut data))
+                    .collect::<Result<Vec<_>, _>>()?
+            } else {
+                read_xor(&mut data, count, T::BYTES as u32 * 8)?
+            };
+            if !data.is_empty() {
+                return Err(decode_error("trailing bytes"));
+            }
+            coordinates.extend(x_bits.into_iter().zip(y_bits).map(|(x, y)| Coord {
+                x: T::from_bits_u64(x),
+                y: T::from_bits_u64(y),
+            }));
+        }
+        PiecewiseLinearFunction::try_new(coordinates)
+    }
+}
+
+/// A function encoded with `BinaryEncoding::Raw`, read in place without copying its points.
+///
+/// The points are checked once when the view is created.
+#[derive(Copy, Clone, Debug)]
+pub struct BinaryView<'a, T: BinaryFloat> {
+    points: &'a [u8],
+    len: usize,
+    _marker: PhantomData<T>,
+}
+
+impl<'a, T: BinaryFloat> BinaryView<'a, T> {
+    /// Creates a view over a function encoded with `BinaryEncoding::Raw`.
+    ///
+    /// Returns `Error::Decode` if the input is not valid, uses another encoding or numeric type,
+    /// and the errors of `PiecewiseLinearFunction::try_new()` if the points are not a valid
+    /// function.
+    pub fn new(bytes: &'a [u8]) -> Result<Self, Error> {
+        let (encoding, count, data) = read_header::<T>(bytes)?;
+        if encoding != BinaryEncoding::Raw {
+            return Err(decode_error("views require the raw encoding"));
+        }
+        let view = Self::from_points(data, count)?;
+        if view.len < 2 {
+            return Err(Error::TooFewPoints);
+        }
+        let mut previous = view.coordinate(0).x;
+        let mut has_finite = previous.is_finite();
+        for i in 1..view.len {
+            let x = view.coordinate(i).x;
+            if previous.partial_cmp(&x) != Some(Ordering::Less) {
+                return Err(Error::UnsortedPoints);
+            }
+            has_finite |= x.is_finite();
+            previous = x;
+        }
+        if !has_finite {
+            return Err(Error::NoFinitePoint);
+        }
+        Ok(view)
+    }
+
+    fn from_points(points: &'a [u8], len: usize) -> Result<Self, Error> {
+        if len.checked_mul(2 * T::BYTES) != Some(points.len()) {
+            return Err(decode_error(
+                "the number of points does not match the length",
+            ));
+        }
+        Ok(BinaryView {
+            points,
+            len,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Returns the number of points of the function.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether the function has no points, which is never the case for valid views.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the `i`-th point of the function.
+    ///
+    /// Panics if `i` is out of bounds.
+    pub fn coordinate(&self, i: usize) -> Coord<T> {
+        let mut point = &self.points[i * 2 * T::BYTES..(i + 1) * 2 * T::BYTES];
+        // These unwraps are guaranteed to succeed as the slice holds exactly two values.
+        let x = read_fixed::<T>(&mut point).unwrap();
+        let y = read_fixed::<T>(&mut point).unwrap();
+        Coord {
+            x: T::from_bits_u64(x),
+            y: T::from_bits_u64(y),
+        }
+    }
+
+    /// Returns the domain of the function.
+    pub fn domain(&self) -> (T, T) {
+        (self.coordinate(0).x, self.coordinate(self.len - 1).x)
+    }
+
+    /// Computes the value f(x), with the same result as `y_at_x()` on the decoded function.
+    ///
+    /// Returns `None` if `x` is outside the domain of f.
+    pub fn y_at_x(&self, x: T) -> Option<T> {
+        // Find the first point whose x value is at least `x`.
+        let (mut lo, mut hi) = (0, self.len);
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            if self.coordinate(mid).x < x {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        let end = match lo {
+            0 if self.coordinate(0).x == x => 1,
+            0 => return None,
+            i if i == self.len => return None,
+            i => i,
+        };
+        let segment = Line::new(self.coordinate(end - 1), self.coordinate(end));
+        Some(y_at_x(&segment, x))
+    }
+
+    /// Copies the points into an owned function.
+    pub fn to_function(&self) -> PiecewiseLinearFunction<T> {
+        PiecewiseLinearFunction {
+            coordinates: (0..self.len).map(|i| self.coordinate(i)).collect(),
+        }
+    }
+}
+
+fn decode_error(message: &str) -> Error {
+    Error::Decode(message.to_string())
+}
+
+/// Checks the header, and returns the encoding, the number of points, and the remaining bytes.
+fn read_header<T: BinaryFloat>(bytes: &[u8]) -> Result<(BinaryEncoding, usize, &[u8]), Error> {
+    if bytes.len() < HEADER_LENGTH || &bytes[..MAGIC.len()] != MAGIC {
+        return Err(decode_error("missing header"));
+    }
+    let (version, size, tag) = (bytes[3], bytes[4], bytes[5]);
+    if version != VERSION {
+        return Err(Error::Decode(format!("unsupported version {}", version)));
+    }
+    if usize::from(size) != T::BYTES {
+        return Err(Error::Decode(format!(
+            "expected {}-byte values, found {}-byte values",
+            T::BYTES,
+            size
+        )));
+    }
+    let encoding = BinaryEncoding::from_tag(tag)
+        .ok_or_else(|| Error::Decode(format!("unknown encoding {}", tag)))?;
+    let mut data = &bytes[HEADER_LENGTH..];
+    let count = read_varint(&mut data)?;
+    let count = usize::try_from(count).map_err(|_| decode_error("too many points"))?;
+    Ok((encoding, count, data))
+}
+
+fn write_fixed<T: BinaryFloat>(bytes: &mut Vec<u8>, bits: u64) {
+    bytes.extend_from_slice(&bits.to_le_bytes()[..T::BYTES]);
+}
+
+fn read_fixed<T: BinaryFloat>(data: &mut &[u8]) -> Result<u64, Error> {
+    if data.len() < T::BYTES {
+        return Err(decode_error("unexpected end of input"));
+    }
+    let mut buffer = [0; 8];
+    buffer[..T::BYTES].copy_from_slice(&data[..T::BYTES]);
+    *data = &data[T::BYTES..];
+    Ok(u64::from_le_bytes(buffer))
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+fn read_varint(data: &mut &[u8]) -> Result<u64, Error> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let (&byte, rest) = data
+            .split_first()
+            .ok_or_else(|| decode_error("unexpected end of input"))?;
+        *data = rest;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(decode_error("varint is too long"))
+}
+
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+fn unzigzag(value: u64) -> i64 {
+    ((value >> 1) as i64) ^ -((value & 1) as i64)
+}
+
+/// Writes bits most significant first into bytes, padding the last byte with zeros.
+struct BitWriter<'a> {
+    bytes: &'a mut Vec<u8>,
+    /// Number of bits used in the last byte.
+    used: u32,
+}
+
+impl<'a> BitWriter<'a> {
+    fn write(&mut self, value: u64, bits: u32) {
+        for i in (0..bits).rev() {
+            if self.used == 0 {
+                self.bytes.push(0);
+            }
+            if (value >> i) & 1 == 1 {
+                // This unwrap is guaranteed to succeed as a byte was pushed above.
+                *self.bytes.last_mut().unwrap() |= 0x80 >> self.used;
+            }
+            self.used = (self.used + 1) % 8;
+        }
+    }
+}
+
+/// Reads bits written by a `BitWriter`.
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn read(&mut self, bits: u32) -> Result<u64, Error> {
+        let mut value = 0;
+        for _ in 0..bits {
+            let byte = self
+                .bytes
+                .get(self.position / 8)
+                .ok_or_else(|| decode_error("unexpected end of input"))?;
+            value = (value << 1) | u64::from((byte >> (7 - self.position % 8)) & 1);
+            self.position += 1;
+        }
+        Ok(value)
+    }
+}
+
+/// Writes values of `width` bits, each XORed with the previous one. Identical values take a
+/// single bit, and the meaningful bits of the XOR are stored in the same window as the previous
+/// one when they fit in it.
+fn write_xor(bytes: &mut Vec<u8>, values: impl Iterator<Item = u64>, width: u32) {
+    let mut writer = BitWriter { bytes, used: 0 };
+    let mut previous: Option<u64> = None;
+    let mut window: Option<(u32, u32)> = None;
+    for value in values {
+        let p = match previous {
+            None => {
+                writer.write(value, width);
+                previous = Some(value);
+                continue;
+            }
+            Some(p) => p,
+        };
+        previous = Some(value);
+        let xor = value ^ p;
+        if xor == 0 {
+            writer.write(0, 1);
+            continue;
+        }
+        writer.write(1, 1);
+        let leading = (xor.leading_zeros() - (64 - width)).min(31);
+        let trailing = xor.trailing_zeros();
+        match window {
+            Some((l, t)) if leading >= l && trailing >= t => {
+                writer.write(0, 1);
+                writer.write(xor >> t, width - l - t);
+            }
+            _ => {
+                let meaningful = width - leading - trailing;
+                writer.write(1, 1);
+                writer.write(u64::from(leading), 5);
+                // A length of 64 does not fit in 6 bits, and is stored as 0.
+                writer.write(u64::from(meaningful % 64), 6);
+                writer.write(xor >> trailing, meaningful);
+                window = Some((leading, trailing));
+            }
+        }
+    }
+}
+
+/// Reads `count` values written by `write_xor()`, and advances `data` past them.
+fn read_xor(data: &mut &[u8], count: usize, width: u32) -> Result<Vec<u64>, Error> {
+    let mut reader = BitReader {
+        bytes: data,
+        position: 0,
+    };
+    let mut values = Vec::with_capacity(count.min(data.len() * 8));
+    let mut window = (0, 0);
+    for i in 0..count {
+        if i == 0 {
+            values.push(reader.read(width)?);
+            continue;
+        }
+        let previous = values[i - 1];
+        if reader.read(1)? == 0 {
+            values.push(previous);
+            continue;
+        }
+        if reader.read(1)? == 1 {
+            let leading = reader.read(5)? as u32;
+            let meaningful = match reader.read(6)? as u32 {
+                0 => 64,
+                m => m,
+            };
+            if leading + meaningful > width {
+                return Err(decode_error("invalid XOR window"));
+            }
+            window = (leading, width - leading - meaningful);
+        }
+        let (leading, trailing) = window;
+        let xor = reader.read(width - leading - trailing)? << trailing;
+        values.push(previous ^ xor);
+    }
+    *data = &data[reader.position.div_ceil(8)..];
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use super::*;
+
+    const ENCODINGS: [BinaryEncoding; 3] = [
+        BinaryEncoding::Raw,
+        BinaryEncoding::Delta,
+        BinaryEncoding::DeltaXor,
+    ];
+
+    fn get_test_function() -> PiecewiseLinearFunction<f64> {
+        PiecewiseLinearFunction::try_from(vec![
+            (f64::NEG_INFINITY, -0.5),
+            (-3., 1e-300),
+            (-0., 0.),
+            (0.1, 0.1),
+            (0.2, 0.1),
+            (7.5, -12345.678),
+            (1e10, f64::MAX),
+            (f64::INFINITY, 2.),
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let f = get_test_function();
+        for &encoding in &ENCODINGS {
+            let bytes = f.to_bytes(encoding);
+            assert_eq!(PiecewiseLinearFunction::from_bytes(&bytes), Ok(f.clone()));
+        }
+
+        let g = PiecewiseLinearFunction::try_from(vec![(0f32, f32::NAN), (1., -0.), (2., 1e-40)])
+            .unwrap();
+        for &encoding in &ENCODINGS {
+            let decoded =
+                PiecewiseLinearFunction::<f32>::from_bytes(&g.to_bytes(encoding)).unwrap();
+            for (a, b) in decoded.coordinates.iter().zip(&g.coordinates) {
+                assert_eq!(a.x.to_bits(), b.x.to_bits());
+                assert_eq!(a.y.to_bits(), b.y.to_bits());
+            }
+        }
+    }
+
+    #[test]
+    fn test_compression() {
+        // A regularly sampled, slowly varying curve.
+        let f = PiecewiseLinearFunction::try_from(
+            (0..1000)
+                .map(|i| (f64::from(i), (f64::from(i / 50) * 0.25).round()))
+                .collect::<Vec<_>>(),
+        )
+        .unwrap();
+        let raw = f.to_bytes(BinaryEncoding::Raw).len();
+        let delta = f.to_bytes(BinaryEncoding::Delta).len();
+        let xor = f.to_bytes(BinaryEncoding::DeltaXor).len();
+        assert!(delta < raw * 3 / 4);
+        assert!(xor < raw / 4);
+        assert_eq!(
+            PiecewiseLinearFunction::from_bytes(&f.to_bytes(BinaryEncoding::DeltaXor)),
+            Ok(f)
+        );
+    }
+
+    #[test]
+    fn test_view() {
+        let f = get_test_function();
+        let bytes = f.to_bytes(BinaryEncoding::Raw);
+        let view = BinaryView::<f64>::new(&bytes).unwrap();
+        assert_eq!(view.len(), f.coordinates.len());
+        assert_eq!(view.domain(), f.domain());
+        assert_eq!(view.to_function(), f);
+        for &x in &[
+            -1e20,
+            -3.,
+            -2.,
+            0.,
+            0.05,
+            0.1,
+            0.15,
+            3.,
+            1e10,
+            1e11,
+            f64::INFINITY,
+        ] {
+            assert_eq!(view.y_at_x(x), f.y_at_x(x), "at {}", x);
+        }
+        let bounded = PiecewiseLinearFunction::try_from(vec![(0., 1.), (2., 3.)]).unwrap();
+        let bytes = bounded.to_bytes(BinaryEncoding::Raw);
+        let view = BinaryView::<f64>::new(&bytes).unwrap();
+        assert_eq!(view.y_at_x(-1.), None);
+        assert_eq!(view.y_at_x(0.), Some(1.));
+        assert_eq!(view.y_at_x(2.), Some(3.));
+        assert_eq!(view.y_at_x(3.), None);
+    }
+
+    #[test]
+    fn test_errors() {
+        let f = get_test_function();
+        let bytes = f.to_bytes(BinaryEncoding::DeltaXor);
+        assert!(matches!(
+            PiecewiseLinearFunction::<f32>::from_bytes(&bytes),
+            Err(Error::Decode(_))
+        ));
+        assert!(matches!(
+            BinaryView::<f64>::new(&bytes),
+            Err(Error::Decode(_))
+        ));
+        for length in 0..bytes.len() {
+            assert!(PiecewiseLinearFunction::<f64>::from_bytes(&bytes[..length]).is_err());
+        }
+        let mut wrong_version = bytes.clone();
+        wrong_version[3] = 2;
+        assert_eq!(
+            PiecewiseLinearFunction::<f64>::from_bytes(&wrong_version),
+            Err(Error::Decode("unsupported version 2".to_string()))
+        );
+
+        // Swap the first two points of a raw function.
+        let mut unsorted = PiecewiseLinearFunction::try_from(vec![(0., 1.), (2., 3.)])
+            .unwrap()
+            .to_bytes(BinaryEncoding::Raw);
+        let points = unsorted.split_off(HEADER_LENGTH + 1);
+        unsorted.extend_from_slice(&points[16..]);
+        unsorted.extend_from_slice(&points[..16]);
+        assert_eq!(
+            BinaryView::<f64>::new(&unsorted).unwrap_err(),
+            Error::UnsortedPoints
+        );
+        assert_eq!(
+            PiecewiseLinearFunction::<f64>::from_bytes(&unsorted),
+            Err(Error::UnsortedPoints)
+        );
+    }
+}
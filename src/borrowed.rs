@@ -0,0 +1,406 @@
+// Copyright 2019 Matthieu Felix
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Piecewise linear functions borrowing their points from an external slice.
+
+use std::collections::BinaryHeap;
+
+use num_traits::Signed;
+
+use crate::{
+    argmax, argmin, bogus_compare, edge_integral, line_intersect, segment_slope, y_at_x, Coord,
+    CoordFloat, Error, Line, PiecewiseLinearFunction, PointsOfInflectionIterator, SegmentsIterator,
+};
+
+/// A continuous piecewise linear function whose points are borrowed from a slice, for instance a
+/// buffer shared between many functions.
+///
+/// The points are checked once, when the view is created, and follow the same invariants as the
+/// coordinates of a `PiecewiseLinearFunction`. Operations which create new functions return
+/// owned `PiecewiseLinearFunction`s.
+///
+/// ## Example
+///
+/// ```
+/// use piecewise_linear::{Coord, PiecewiseLinearFunctionRef};
+/// let buffer = vec![
+///     Coord { x: 0., y: 0. },
+///     Coord { x: 1., y: 1. },
+///     Coord { x: 0., y: 2. },
+///     Coord { x: 1., y: 0. },
+/// ];
+/// let f = PiecewiseLinearFunctionRef::new(&buffer[..2]).unwrap();
+/// let g = PiecewiseLinearFunctionRef::new(&buffer[2..]).unwrap();
+/// assert_eq!(f.y_at_x(0.5), Some(0.5));
+/// assert_eq!(f.add(g).unwrap().y_at_x(0.5), Some(1.5));
+/// ```
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct PiecewiseLinearFunctionRef<'a, T: CoordFloat> {
+    coordinates: &'a [Coord<T>],
+}
+
+impl<'a, T: CoordFloat> PiecewiseLinearFunctionRef<'a, T> {
+    /// Creates a view over a slice of coordinates, returning an error describing which invariant
+    /// of `PiecewiseLinearFunction` was not respected, if any.
+    pub fn new(coordinates: &'a [Coord<T>]) -> Result<Self, Error> {
+        if coordinates.len() < 2 {
+            Err(Error::TooFewPoints)
+        } else if !coordinates.windows(2).all(|w| w[0].x < w[1].x) {
+            Err(Error::UnsortedPoints)
+        } else if !coordinates.iter().any(|c| c.x.is_finite()) {
+            Err(Error::NoFinitePoint)
+        } else {
+            Ok(PiecewiseLinearFunctionRef { coordinates })
+        }
+    }
+
+    /// Returns the borrowed points of the function.
+    pub fn coordinates(&self) -> &'a [Coord<T>] {
+        self.coordinates
+    }
+
+    /// Copies the points into an owned function.
+    pub fn to_function(&self) -> PiecewiseLinearFunction<T> {
+        PiecewiseLinearFunction {
+            coordinates: self.coordinates.to_vec(),
+        }
+    }
+
+    /// Returns a function's domain, represented as its min and max.
+    pub fn domain(&self) -> (T, T) {
+        // This unwrap is guaranteed to succeed as there are at least two coordinates.
+        (self.coordinates[0].x, self.coordinates.last().unwrap().x)
+    }
+
+    /// Returns the slopes of the first and last segments of this function.
+    pub fn edge_slopes(&self) -> (T, T) {
+        let last_index = self.coordinates.len() - 1;
+        (
+            segment_slope(&Line::new(self.coordinates[0], self.coordinates[1])),
+            segment_slope(&Line::new(
+                self.coordinates[last_index - 1],
+                self.coordinates[last_index],
+            )),
+        )
+    }
+
+    /// Returns an iterator over the segments of f.
+    ///
+    /// See `segments_iter()` on `PiecewiseLinearFunction` for details.
+    pub fn segments_iter(&self) -> SegmentsIterator<'a, T> {
+        SegmentsIterator(self.coordinates.iter().peekable())
+    }
+
+    /// Returns an iterator over the joint points of inflection of `self` and `other`, or `None` if
+    /// their domains differ.
+    pub fn points_of_inflection_iter<'b>(
+        &self,
+        other: PiecewiseLinearFunctionRef<'b, T>,
+    ) -> Option<PointsOfInflectionIterator<'b, T>>
+    where
+        'a: 'b,
+    {
+        if self.domain() != other.domain() {
+            None
+        } else {
+            Some(PointsOfInflectionIterator {
+                segment_iterators: vec![
+                    self.segments_iter().peekable(),
+                    other.segments_iter().peekable(),
+                ],
+                heap: BinaryHeap::new(),
+                initial: true,
+            })
+        }
+    }
+
+    /// Returns a segment `((x1, y1), (x2, y2))` of this function such that `x1 <= x <= x2`.
+    ///
+    /// Returns `None` if `x` is outside the domain of f.
+    pub fn segment_at_x(&self, x: T) -> Option<Line<T>> {
+        let idx = match self
+            .coordinates
+            .binary_search_by(|val| bogus_compare(&val.x, &x))
+        {
+            Ok(idx) => idx,
+            Err(idx) => {
+                if idx == 0 || idx == self.coordinates.len() {
+                    // Outside the function's domain
+                    return None;
+                } else {
+                    idx
+                }
+            }
+        };
+
+        if idx == 0 {
+            Some(Line::new(self.coordinates[idx], self.coordinates[idx + 1]))
+        } else {
+            Some(Line::new(self.coordinates[idx - 1], self.coordinates[idx]))
+        }
+    }
+
+    /// Computes the value f(x) for this piecewise linear function.
+    ///
+    /// Returns `None` if `x` is outside the domain of f.
+    pub fn y_at_x(&self, x: T) -> Option<T> {
+        self.segment_at_x(x).map(|line| y_at_x(&line, x))
+    }
+
+    /// Sums this function with another one.
+    ///
+    /// Both functions must have the same domain; returns `None` otherwise.
+    pub fn add(
+        &self,
+        other: PiecewiseLinearFunctionRef<'_, T>,
+    ) -> Option<PiecewiseLinearFunction<T>> {
+        self.points_of_inflection_iter(other).map(|poi| {
+            PiecewiseLinearFunction::new(
+                poi.map(|(x, coords)| Coord {
+                    x,
+                    y: coords[0] + coords[1],
+                })
+                .collect(),
+            )
+            // This unwrap is guaranteed to succeed as the starting POI has generates ordered x,
+            // which do not get modified.
+            .unwrap()
+        })
+    }
+
+    /// Returns a new piecewise linear function that is the maximum of `self` and `other`.
+    ///
+    /// Returns `None` if the domains of `self` and `other` are not equal.
+    pub fn max(
+        &self,
+        other: PiecewiseLinearFunctionRef<'_, T>,
+    ) -> Option<PiecewiseLinearFunction<T>> {
+        let mut poi_iter = self.points_of_inflection_iter(other)?;
+        let mut new_values = Vec::new();
+
+        let (x, values) = poi_iter.next().unwrap();
+        // At -inf, `values` holds slopes, and the largest function is the one that decreases the
+        // fastest.
+        let (i_largest, largest) = if x == T::neg_infinity() {
+            argmin(&values).unwrap()
+        } else {
+            argmax(&values).unwrap()
+        };
+        new_values.push(Coord { x, y: *largest });
+
+        let mut prev_largest = i_largest;
+        let mut prev_x = x;
+        let mut prev_values = values;
+
+        for (x, values) in poi_iter {
+            let (i_largest, largest) = argmax(&values).unwrap();
+            if i_largest != prev_largest {
+                let (inter_x, inter_y) = line_intersect(
+                    &Line::new((prev_x, prev_values[0]), (x, values[0])),
+                    &Line::new((prev_x, prev_values[1]), (x, values[1])),
+                );
+                // This condition seems necessary as argmax() is likely unstable, so i_largest
+                // can change even if two lines remain equal.
+                if inter_x > prev_x && inter_x < x {
+                    new_values.push(Coord {
+                        x: inter_x,
+                        y: inter_y,
+                    });
+                }
+            }
+            new_values.push(Coord { x, y: *largest });
+            prev_largest = i_largest;
+            prev_x = x;
+            prev_values = values;
+        }
+
+        Some(PiecewiseLinearFunction::new(new_values).unwrap())
+    }
+}
+
+impl<'a, T: CoordFloat + Signed> PiecewiseLinearFunctionRef<'a, T> {
+    /// Returns -f.
+    pub fn negate(&self) -> PiecewiseLinearFunction<T> {
+        PiecewiseLinearFunction::new(
+            self.coordinates
+                .iter()
+                .map(|Coord { x, y }| Coord { x: *x, y: -(*y) })
+                .collect(),
+        )
+        // This unwrap is guaranteed to succeed because the coordinate's x values haven't changed.
+        .unwrap()
+    }
+
+    /// Computes the minimum of this function and `other`.
+    ///
+    /// Returns `None` in case of a domain error.
+    pub fn min(
+        &self,
+        other: PiecewiseLinearFunctionRef<'_, T>,
+    ) -> Option<PiecewiseLinearFunction<T>> {
+        Some(self.negate().max(&other.negate())?.negate())
+    }
+}
+
+impl<'a, T: CoordFloat + ::std::iter::Sum> PiecewiseLinearFunctionRef<'a, T> {
+    /// Returns the integral of the considered function over its entire domain.
+    ///
+    /// See `integrate()` on `PiecewiseLinearFunction` for details.
+    pub fn integrate(&self) -> T {
+        self.segments_iter()
+            .map(|segment| {
+                if segment.end.x.is_infinite() {
+                    edge_integral(segment.start.y, segment.end.y)
+                } else if segment.start.x.is_infinite() {
+                    // Integrating towards -inf is integrating the mirrored line towards +inf.
+                    edge_integral(segment.end.y, -segment.start.y)
+                } else {
+                    let x_span = segment.end.x - segment.start.x;
+                    x_span * (segment.start.y + segment.end.y) / T::from(2).unwrap()
+                }
+            })
+            .sum()
+    }
+}
+
+impl<T: CoordFloat> PiecewiseLinearFunction<T> {
+    /// Returns a view borrowing the points of this function.
+    pub fn as_view(&self) -> PiecewiseLinearFunctionRef<'_, T> {
+        PiecewiseLinearFunctionRef {
+            coordinates: &self.coordinates,
+        }
+    }
+}
+
+impl<'a, T: CoordFloat> From<&'a PiecewiseLinearFunction<T>> for PiecewiseLinearFunctionRef<'a, T> {
+    fn from(f: &'a PiecewiseLinearFunction<T>) -> Self {
+        f.as_view()
+    }
+}
+
+impl<'a, T: CoordFloat> From<PiecewiseLinearFunctionRef<'a, T>> for PiecewiseLinearFunction<T> {
+    fn from(f: PiecewiseLinearFunctionRef<'a, T>) -> Self {
+        f.to_function()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use super::*;
+
+    fn get_buffer() -> Vec<Coord<f64>> {
+        vec![
+            (0., 0.).into(),
+            (1., 1.).into(),
+            (2., 1.5).into(),
+            (0., 2.).into(),
+            (1.5, -1.).into(),
+            (2., 0.).into(),
+        ]
+    }
+
+    #[test]
+    fn test_new() {
+        let buffer = get_buffer();
+        assert!(PiecewiseLinearFunctionRef::new(&buffer[..3]).is_ok());
+        assert_eq!(
+            PiecewiseLinearFunctionRef::new(&buffer[..1]),
+            Err(Error::TooFewPoints)
+        );
+        assert_eq!(
+            PiecewiseLinearFunctionRef::new(&buffer[1..5]),
+            Err(Error::UnsortedPoints)
+        );
+        let infinite = [
+            Coord {
+                x: f64::NEG_INFINITY,
+                y: 0.,
+            },
+            Coord {
+                x: f64::INFINITY,
+                y: 0.,
+            },
+        ];
+        assert_eq!(
+            PiecewiseLinearFunctionRef::new(&infinite),
+            Err(Error::NoFinitePoint)
+        );
+    }
+
+    #[test]
+    fn test_matches_owned() {
+        let buffer = get_buffer();
+        let f = PiecewiseLinearFunctionRef::new(&buffer[..3]).unwrap();
+        let g = PiecewiseLinearFunctionRef::new(&buffer[3..]).unwrap();
+        let function = |points: Vec<(f64, f64)>| PiecewiseLinearFunction::try_from(points).unwrap();
+
+        assert_eq!(
+            f.to_function(),
+            function(vec![(0., 0.), (1., 1.), (2., 1.5)])
+        );
+        assert_eq!(f.to_function().as_view(), f);
+        assert_eq!(f.domain(), (0., 2.));
+        assert_eq!(f.edge_slopes(), (1., 0.5));
+        assert_eq!(
+            f.segments_iter().collect::<Vec<_>>(),
+            vec![
+                Line::new((0., 0.), (1., 1.)),
+                Line::new((1., 1.), (2., 1.5))
+            ]
+        );
+        let values = [
+            (-1., None),
+            (0., Some(0.)),
+            (0.5, Some(0.5)),
+            (1., Some(1.)),
+            (1.5, Some(1.25)),
+            (2., Some(1.5)),
+            (3., None),
+        ];
+        for &(x, y) in &values {
+            assert_eq!(f.y_at_x(x), y);
+        }
+        assert_eq!(f.integrate(), 1.75);
+        assert_eq!(
+            f.add(g),
+            Some(function(vec![(0., 2.), (1., 1.), (1.5, 0.25), (2., 1.5)]))
+        );
+        assert_eq!(
+            f.max(g),
+            Some(function(vec![
+                (0., 2.),
+                (2. / 3., 2. / 3.),
+                (1., 1.),
+                (1.5, 1.25),
+                (2., 1.5)
+            ]))
+        );
+        assert_eq!(
+            f.min(g),
+            Some(function(vec![
+                (0., 0.),
+                (2. / 3., 2. / 3.),
+                (1., 0.),
+                (1.5, -1.),
+                (2., 0.)
+            ]))
+        );
+        assert_eq!(f.negate(), function(vec![(0., 0.), (1., -1.), (2., -1.5)]));
+
+        let short = PiecewiseLinearFunctionRef::new(&buffer[..2]).unwrap();
+        assert_eq!(f.add(short), None);
+    }
+}
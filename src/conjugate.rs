@@ -0,0 +1,197 @@
+// Copyright 2019 Matthieu Felix
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Legendre–Fenchel transform (convex conjugate) of piecewise linear functions.
+
+use crate::{finite_endpoint, segment_slope, Coord, CoordFloat, Error, PiecewiseLinearFunction};
+
+/// Controls how `conjugate()` on `PiecewiseLinearFunction` handles functions which are not convex.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ConjugateStrategy {
+    /// Return `Error::NotConvex` if the function is not convex.
+    RequireConvex,
+    /// Compute the conjugate of the convex minorant of the function. This is also the conjugate of
+    /// the function itself, but it is not an involution anymore.
+    ConvexMinorant,
+}
+
+impl<T: CoordFloat> PiecewiseLinearFunction<T> {
+    /// Computes the Legendre–Fenchel transform (or convex conjugate) of this function, that is,
+    /// the function f*(s) = sup { s * x - f(x) }, where the supremum is taken over the domain of f.
+    ///
+    /// The points of inflection of f* are at the slopes of f, and its slopes are the _x_ values of
+    /// the points of inflection of f. In particular, f* is defined over all real numbers if the
+    /// domain of f is bounded, and its domain is bounded by the edge slopes of f on sides where the
+    /// domain of f is infinite. For convex functions, the conjugate of f* is f itself.
+    ///
+    /// Returns `Error::NotConvex` if f is not convex and `strategy` is
+    /// `ConjugateStrategy::RequireConvex`, `Error::Unbounded` if f* is +inf everywhere, and
+    /// `Error::TooFewPoints` if f* is only finite at a single point (which happens when f is a
+    /// line over all real numbers).
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use piecewise_linear::{ConjugateStrategy, PiecewiseLinearFunction};
+    /// use std::convert::TryFrom;
+    /// let f = PiecewiseLinearFunction::try_from(vec![(0., 0.), (1., 0.), (2., 1.)]).unwrap();
+    /// let conjugate = f.conjugate(ConjugateStrategy::RequireConvex).unwrap();
+    /// let inf = std::f64::INFINITY;
+    /// assert_eq!(
+    ///     conjugate,
+    ///     PiecewiseLinearFunction::try_from(vec![(-inf, 0.), (0., 0.), (1., 1.), (inf, 2.)])
+    ///         .unwrap()
+    /// );
+    /// assert_eq!(conjugate.conjugate(ConjugateStrategy::RequireConvex).unwrap(), f);
+    /// ```
+    pub fn conjugate(
+        &self,
+        strategy: ConjugateStrategy,
+    ) -> Result<PiecewiseLinearFunction<T>, Error> {
+        if !self.is_convex() {
+            return match strategy {
+                ConjugateStrategy::RequireConvex => Err(Error::NotConvex),
+                ConjugateStrategy::ConvexMinorant => self
+                    .convex_minorant()
+                    .ok_or(Error::Unbounded)?
+                    .conjugate(ConjugateStrategy::RequireConvex),
+            };
+        }
+
+        let mut coordinates = Vec::with_capacity(self.coordinates.len() + 1);
+        let (start, end) = self.domain();
+        if start.is_finite() {
+            coordinates.push(Coord {
+                x: T::neg_infinity(),
+                y: start,
+            });
+        }
+        for segment in self.segments_iter() {
+            let slope = segment_slope(&segment);
+            // Segments with the same slope give the same point.
+            if coordinates.last().map(|c: &Coord<T>| c.x) == Some(slope) {
+                continue;
+            }
+            let anchor = finite_endpoint(&segment);
+            coordinates.push(Coord {
+                x: slope,
+                y: slope * anchor.x - anchor.y,
+            });
+        }
+        if end.is_finite() {
+            coordinates.push(Coord {
+                x: T::infinity(),
+                y: end,
+            });
+        }
+        PiecewiseLinearFunction::new(coordinates).ok_or(Error::TooFewPoints)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use super::*;
+
+    /// Computes f*(s) by evaluating s * x - f(x) at all points of inflection of f.
+    fn brute_force_conjugate(f: &PiecewiseLinearFunction<f64>, s: f64) -> f64 {
+        f.coordinates
+            .iter()
+            .map(|c| s * c.x - c.y)
+            .fold(f64::NEG_INFINITY, f64::max)
+    }
+
+    #[test]
+    fn test_conjugate() {
+        let f = PiecewiseLinearFunction::try_from(vec![
+            (-1., 3.),
+            (0., 1.),
+            (1., 0.),
+            (2., 0.),
+            (4., 3.),
+        ])
+        .unwrap();
+        let conjugate = f.conjugate(ConjugateStrategy::RequireConvex).unwrap();
+        assert_eq!(conjugate.domain(), (f64::NEG_INFINITY, f64::INFINITY));
+        for i in -40..=40 {
+            let s = f64::from(i) / 10.;
+            assert!((conjugate.y_at_x(s).unwrap() - brute_force_conjugate(&f, s)).abs() < 1e-12);
+        }
+        assert_eq!(
+            conjugate
+                .conjugate(ConjugateStrategy::RequireConvex)
+                .unwrap(),
+            f
+        );
+    }
+
+    #[test]
+    fn test_conjugate_infinite_domain() {
+        let f = PiecewiseLinearFunction::with_edge_slopes(
+            vec![(0., 0.).into(), (1., 1.).into()],
+            None,
+            Some(2.),
+        )
+        .unwrap();
+        let conjugate = f.conjugate(ConjugateStrategy::RequireConvex).unwrap();
+        assert_eq!(
+            conjugate,
+            PiecewiseLinearFunction::try_from(vec![(f64::NEG_INFINITY, 0.), (1., 0.), (2., 1.)])
+                .unwrap()
+        );
+        assert_eq!(
+            conjugate
+                .conjugate(ConjugateStrategy::RequireConvex)
+                .unwrap(),
+            f
+        );
+
+        let line = PiecewiseLinearFunction::with_edge_slopes(
+            vec![(0f64, 1.).into(), (1., 2.).into()],
+            Some(1.),
+            Some(1.),
+        )
+        .unwrap();
+        assert_eq!(
+            line.conjugate(ConjugateStrategy::RequireConvex),
+            Err(Error::TooFewPoints)
+        );
+    }
+
+    #[test]
+    fn test_conjugate_not_convex() {
+        let f = PiecewiseLinearFunction::try_from(vec![(0., 0.), (1., 2.), (2., 1.), (3., 3.)])
+            .unwrap();
+        assert_eq!(
+            f.conjugate(ConjugateStrategy::RequireConvex),
+            Err(Error::NotConvex)
+        );
+        assert_eq!(
+            f.conjugate(ConjugateStrategy::ConvexMinorant).unwrap(),
+            f.convex_minorant()
+                .unwrap()
+                .conjugate(ConjugateStrategy::RequireConvex)
+                .unwrap()
+        );
+
+        let unbounded =
+            PiecewiseLinearFunction::with_edge_slopes(vec![(0f64, 0.).into()], Some(1.), Some(-1.))
+                .unwrap();
+        assert_eq!(
+            unbounded.conjugate(ConjugateStrategy::ConvexMinorant),
+            Err(Error::Unbounded)
+        );
+    }
+}
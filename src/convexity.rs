@@ -0,0 +1,285 @@
+// Copyright 2019 Matthieu Felix
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Convexity and monotonicity tests, and convex and concave hulls.
+
+use std::cmp::Ordering;
+
+use crate::envelope::Extremum;
+use crate::{segment_slope, Coord, CoordFloat, Line, PiecewiseLinearFunction};
+
+impl<T: CoordFloat> PiecewiseLinearFunction<T> {
+    /// Checks whether this function is convex, that is, whether its slopes are non-decreasing.
+    pub fn is_convex(&self) -> bool {
+        has_sorted_slopes(self, Ordering::Less)
+    }
+
+    /// Checks whether this function is concave, that is, whether its slopes are non-increasing.
+    pub fn is_concave(&self) -> bool {
+        has_sorted_slopes(self, Ordering::Greater)
+    }
+
+    /// Checks whether this function is non-decreasing, or increasing if `strict` is true.
+    pub fn is_increasing(&self, strict: bool) -> bool {
+        self.segments_iter().all(|s| {
+            let slope = segment_slope(&s);
+            slope > T::zero() || (!strict && slope == T::zero())
+        })
+    }
+
+    /// Checks whether this function is non-increasing, or decreasing if `strict` is true.
+    pub fn is_decreasing(&self, strict: bool) -> bool {
+        self.segments_iter().all(|s| {
+            let slope = segment_slope(&s);
+            slope < T::zero() || (!strict && slope == T::zero())
+        })
+    }
+
+    /// Checks whether this function is monotone, that is, either non-decreasing or non-increasing.
+    /// If `strict` is true, checks whether it is either increasing or decreasing.
+    pub fn is_monotone(&self, strict: bool) -> bool {
+        self.is_increasing(strict) || self.is_decreasing(strict)
+    }
+
+    /// Returns the convex minorant of this function, that is, the greatest convex function that is
+    /// less than or equal to it over its domain.
+    ///
+    /// The result is the lower convex hull of the points of inflection of the function. Returns
+    /// `None` if there is no such function, which happens when the domain is infinite on both
+    /// sides and the slope at -inf is greater than the slope at +inf.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use piecewise_linear::PiecewiseLinearFunction;
+    /// use std::convert::TryFrom;
+    /// let f = PiecewiseLinearFunction::try_from(vec![(0., 0.), (1., 2.), (2., 1.), (3., 3.)])
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     f.convex_minorant().unwrap(),
+    ///     PiecewiseLinearFunction::try_from(vec![(0., 0.), (2., 1.), (3., 3.)]).unwrap()
+    /// );
+    /// ```
+    pub fn convex_minorant(&self) -> Option<PiecewiseLinearFunction<T>> {
+        hull(self, Extremum::Lower)
+    }
+
+    /// Returns the concave majorant of this function, that is, the least concave function that is
+    /// greater than or equal to it over its domain.
+    ///
+    /// The result is the upper concave hull of the points of inflection of the function. Returns
+    /// `None` if there is no such function, which happens when the domain is infinite on both
+    /// sides and the slope at -inf is less than the slope at +inf.
+    pub fn concave_majorant(&self) -> Option<PiecewiseLinearFunction<T>> {
+        hull(self, Extremum::Upper)
+    }
+}
+
+/// Checks whether the slopes of `f` are sorted: in increasing order for `Ordering::Less`, meaning
+/// that `f` is convex, and in decreasing order for `Ordering::Greater`, meaning that it is concave.
+pub(crate) fn has_sorted_slopes<T: CoordFloat>(
+    f: &PiecewiseLinearFunction<T>,
+    order: Ordering,
+) -> bool {
+    let slopes: Vec<T> = f.segments_iter().map(|s| segment_slope(&s)).collect();
+    slopes
+        .windows(2)
+        .all(|w| w[0] == w[1] || w[0].partial_cmp(&w[1]) == Some(order))
+}
+
+/// Computes the lower convex hull (for `Extremum::Lower`) or upper concave hull (for
+/// `Extremum::Upper`) of `f` with a monotone chain scan.
+fn hull<T: CoordFloat>(
+    f: &PiecewiseLinearFunction<T>,
+    extremum: Extremum,
+) -> Option<PiecewiseLinearFunction<T>> {
+    // Whether slopes must be sorted in increasing order in the hull.
+    let increasing = extremum == Extremum::Lower;
+    // Checks that going from slope `a` to slope `b` does not break convexity (or concavity).
+    let keeps_order = |a: T, b: T| if increasing { a < b } else { a > b };
+    let slope = |a: &Coord<T>, b: &Coord<T>| Line::new(*a, *b).slope();
+
+    let mut points: Vec<Coord<T>> = Vec::with_capacity(f.coordinates.len());
+    for &point in f.coordinates.iter().filter(|c| c.x.is_finite()) {
+        while points.len() >= 2
+            && !keeps_order(
+                slope(&points[points.len() - 2], &points[points.len() - 1]),
+                slope(&points[points.len() - 1], &point),
+            )
+        {
+            points.pop();
+        }
+        points.push(point);
+    }
+
+    let (left_slope, right_slope) = f.edge_slopes();
+    let (start, end) = f.domain();
+    if start.is_infinite() && end.is_infinite() && keeps_order(right_slope, left_slope) {
+        return None;
+    }
+    // Only keep the points which are on the hull when it extends to infinity with the slopes of the
+    // edge pieces.
+    if end.is_infinite() {
+        while points.len() >= 2 {
+            let last_slope = slope(&points[points.len() - 2], &points[points.len() - 1]);
+            if keeps_order(last_slope, right_slope) || last_slope == right_slope {
+                break;
+            }
+            points.pop();
+        }
+    }
+    if start.is_infinite() {
+        let mut first = 0;
+        while first + 1 < points.len() {
+            let first_slope = slope(&points[first], &points[first + 1]);
+            if keeps_order(left_slope, first_slope) || first_slope == left_slope {
+                break;
+            }
+            first += 1;
+        }
+        points.drain(..first);
+        points.insert(
+            0,
+            Coord {
+                x: start,
+                y: left_slope,
+            },
+        );
+    }
+    if end.is_infinite() {
+        points.push(Coord {
+            x: end,
+            y: right_slope,
+        });
+    }
+    PiecewiseLinearFunction::new(points)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use super::*;
+
+    fn get_test_function() -> PiecewiseLinearFunction<f64> {
+        PiecewiseLinearFunction::try_from(vec![(0., 0.), (1., 2.), (2., 1.), (3., 3.)]).unwrap()
+    }
+
+    #[test]
+    fn test_convexity() {
+        let f = get_test_function();
+        assert!(!f.is_convex());
+        assert!(!f.is_concave());
+        let convex =
+            PiecewiseLinearFunction::try_from(vec![(0., 1.), (1., 0.), (2., 0.), (3., 2.)])
+                .unwrap();
+        assert!(convex.is_convex());
+        assert!(!convex.is_concave());
+        assert!(convex.negate().is_concave());
+        let linear = PiecewiseLinearFunction::try_from(vec![(0., 0.), (1., 1.), (2., 2.)]).unwrap();
+        assert!(linear.is_convex() && linear.is_concave());
+    }
+
+    #[test]
+    fn test_monotonicity() {
+        let f = get_test_function();
+        assert!(!f.is_monotone(false));
+        let increasing =
+            PiecewiseLinearFunction::try_from(vec![(0., 0.), (1., 0.), (2., 3.)]).unwrap();
+        assert!(increasing.is_increasing(false));
+        assert!(!increasing.is_increasing(true));
+        assert!(increasing.is_monotone(false));
+        assert!(!increasing.is_monotone(true));
+        assert!(increasing.negate().is_decreasing(false));
+        let constant = PiecewiseLinearFunction::constant((0., 1.), 1.).unwrap();
+        assert!(constant.is_increasing(false) && constant.is_decreasing(false));
+        let unbounded = PiecewiseLinearFunction::with_edge_slopes(
+            vec![(0., 0.).into(), (1., 1.).into()],
+            Some(2.),
+            Some(-1.),
+        )
+        .unwrap();
+        assert!(!unbounded.is_monotone(false));
+    }
+
+    #[test]
+    fn test_hulls() {
+        let f = get_test_function();
+        assert_eq!(
+            f.convex_minorant().unwrap(),
+            PiecewiseLinearFunction::try_from(vec![(0., 0.), (2., 1.), (3., 3.)]).unwrap()
+        );
+        assert_eq!(
+            f.concave_majorant().unwrap(),
+            PiecewiseLinearFunction::try_from(vec![(0., 0.), (1., 2.), (3., 3.)]).unwrap()
+        );
+        let convex = f.convex_minorant().unwrap();
+        assert_eq!(convex.convex_minorant().unwrap(), convex);
+    }
+
+    #[test]
+    fn test_hulls_infinite_domains() {
+        let f = PiecewiseLinearFunction::with_edge_slopes(
+            vec![
+                (0., 0.).into(),
+                (1., 2.).into(),
+                (2., 1.).into(),
+                (3., 3.).into(),
+            ],
+            Some(-1.),
+            Some(1.),
+        )
+        .unwrap();
+        // The ray to +inf starts below the hull of the other points with slope 2.
+        assert_eq!(
+            f.convex_minorant().unwrap(),
+            PiecewiseLinearFunction::try_from(vec![
+                (f64::NEG_INFINITY, -1.),
+                (0., 0.),
+                (2., 1.),
+                (f64::INFINITY, 1.)
+            ])
+            .unwrap()
+        );
+        assert_eq!(f.concave_majorant(), None);
+
+        let g = PiecewiseLinearFunction::with_edge_slopes(
+            vec![(0., 0.).into(), (1., 2.).into(), (2., 1.).into()],
+            Some(3.),
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            g.concave_majorant().unwrap(),
+            PiecewiseLinearFunction::try_from(vec![
+                (f64::NEG_INFINITY, 3.),
+                (0., 0.),
+                (1., 2.),
+                (2., 1.)
+            ])
+            .unwrap()
+        );
+        assert_eq!(
+            g.convex_minorant().unwrap(),
+            PiecewiseLinearFunction::try_from(vec![(f64::NEG_INFINITY, 3.), (2., 1.)]).unwrap()
+        );
+        let wide = PiecewiseLinearFunction::with_edge_slopes(
+            vec![(0., 0.).into(), (1., 2.).into()],
+            Some(3.),
+            Some(-1.),
+        )
+        .unwrap();
+        assert_eq!(wide.convex_minorant(), None);
+    }
+}
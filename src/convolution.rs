@@ -0,0 +1,806 @@
+// Copyright 2019 Matthieu Felix
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Min-plus and max-plus convolutions and deconvolutions of piecewise linear functions.
+
+use std::cmp::Ordering;
+
+use num_traits::Signed;
+
+use crate::convexity::has_sorted_slopes;
+use crate::envelope::{remove_collinear_points, Envelope, Extremum};
+use crate::{
+    bogus_compare, finite_endpoint, segment_slope, y_at_x, Coord, CoordFloat, Line,
+    PiecewiseLinearFunction,
+};
+
+impl<T: CoordFloat> PiecewiseLinearFunction<T> {
+    /// Computes the min-plus convolution of `self` and `other`, that is, the function
+    /// (f ⊗ g)(t) = inf { f(s) + g(t - s) }, where the infimum is taken over all `s` such that both
+    /// `s` and `t - s` are in the domains of f and g respectively.
+    ///
+    /// If f and g have domains [a1, b1] and [a2, b2], the resulting function has domain
+    /// [a1 + a2, b1 + b2]. Domains may be infinite, as for arrival and service curves over
+    /// [0, inf[. Returns `None` if the result is -inf somewhere, which can only happen when one
+    /// function extends to -inf and the other one to +inf.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use piecewise_linear::PiecewiseLinearFunction;
+    /// use std::convert::TryFrom;
+    /// // Two rate-latency service curves, with rates 2 and 3 and latencies 1 and 2.
+    /// let f = PiecewiseLinearFunction::try_from(vec![(0., 0.), (1., 0.), (5., 8.)]).unwrap();
+    /// let g = PiecewiseLinearFunction::try_from(vec![(0., 0.), (2., 0.), (5., 9.)]).unwrap();
+    /// assert_eq!(
+    ///     f.min_plus_convolution(&g).unwrap(),
+    ///     PiecewiseLinearFunction::try_from(vec![(0., 0.), (3., 0.), (7., 8.), (10., 17.)]).unwrap()
+    /// );
+    /// ```
+    ///
+    /// ## Complexity
+    ///
+    /// When both functions are convex, the result is obtained by merging their segments in order of
+    /// increasing slope, in _O(n + m)_ time. When both are concave, the infimum is always reached
+    /// at an edge of the domain of f or g, and the result is the lower envelope of four shifted
+    /// copies of f and g. Otherwise, the result is the lower envelope of the convolutions of all
+    /// pairs of segments, which takes _O(n² m²)_ time in the worst case.
+    ///
+    /// When either domain is infinite, convex functions are still merged, and all other pairs are
+    /// handled by the general method, where the pieces extending to infinity are rays.
+    pub fn min_plus_convolution(
+        &self,
+        other: &PiecewiseLinearFunction<T>,
+    ) -> Option<PiecewiseLinearFunction<T>> {
+        convolution(self, other, Extremum::Lower)
+    }
+
+    /// Computes the max-plus convolution of `self` and `other`, that is, the function
+    /// (f ⊕ g)(t) = sup { f(s) + g(t - s) }, where the supremum is taken over all `s` such that both
+    /// `s` and `t - s` are in the domains of f and g respectively.
+    ///
+    /// If f and g have domains [a1, b1] and [a2, b2], the resulting function has domain
+    /// [a1 + a2, b1 + b2]. Domains may be infinite; returns `None` if the result is +inf
+    /// somewhere, which can only happen when one function extends to -inf and the other one to
+    /// +inf.
+    ///
+    /// ## Complexity
+    ///
+    /// This mirrors `min_plus_convolution()`: concave functions are merged in _O(n + m)_ time,
+    /// the supremum for convex functions is reached at the edges of their domains, and the result is
+    /// otherwise the upper envelope of the convolutions of all pairs of segments.
+    pub fn max_plus_convolution(
+        &self,
+        other: &PiecewiseLinearFunction<T>,
+    ) -> Option<PiecewiseLinearFunction<T>> {
+        convolution(self, other, Extremum::Upper)
+    }
+
+    /// Computes the infimal convolution (or epi-sum) of `self` and `other`, that is, the function
+    /// (f □ g)(x) = inf { f(y) + g(x - y) }, where the infimum is taken over all `y` such that both
+    /// `y` and `x - y` are in the domains of f and g respectively.
+    ///
+    /// This is the same operation as `min_plus_convolution()`: see `infimal_convolution()` for
+    /// details on convex functions over infinite domains. Returns `None` if the result is -inf
+    /// somewhere.
+    pub fn infimal_convolution(
+        &self,
+        other: &PiecewiseLinearFunction<T>,
+    ) -> Option<PiecewiseLinearFunction<T>> {
+        if self.is_convex() && other.is_convex() {
+            convex_infimal_convolution(&[self, other])
+        } else {
+            self.min_plus_convolution(other)
+        }
+    }
+
+    /// Returns the function x -> f(x - dx) + dy.
+    pub(crate) fn translate(&self, dx: T, dy: T) -> PiecewiseLinearFunction<T> {
+        PiecewiseLinearFunction::new(
+            self.coordinates
+                .iter()
+                .map(|c| Coord {
+                    x: c.x + dx,
+                    y: if c.x.is_finite() { c.y + dy } else { c.y },
+                })
+                .collect(),
+        )
+        // This unwrap is guaranteed to succeed because all x values are shifted by the same amount.
+        .unwrap()
+    }
+
+    /// Returns the function x -> f(-x).
+    pub(crate) fn mirror(&self) -> PiecewiseLinearFunction<T> {
+        PiecewiseLinearFunction::new(
+            self.coordinates
+                .iter()
+                .rev()
+                .map(|c| Coord {
+                    x: -c.x,
+                    y: if c.x.is_finite() { c.y } else { -c.y },
+                })
+                .collect(),
+        )
+        // This unwrap is guaranteed to succeed because reversing the order of the points undoes
+        // the reversal of the order of the x values.
+        .unwrap()
+    }
+}
+
+impl<T: CoordFloat + Signed> PiecewiseLinearFunction<T> {
+    /// Computes the min-plus deconvolution of `self` and `other`, that is, the function
+    /// (f ⊘ g)(t) = sup { f(t + u) - g(u) }, where the supremum is taken over all `u` such that both
+    /// `t + u` and `u` are in the domains of f and g respectively.
+    ///
+    /// If f and g have domains [a1, b1] and [a2, b2], the resulting function has domain
+    /// [a1 - b2, b1 - a2]. Returns `None` if the result is +inf somewhere.
+    ///
+    /// This is computed as f ⊕ ǧ, where ǧ(v) = -g(-v); see `max_plus_convolution()` for details
+    /// on the complexity.
+    pub fn min_plus_deconvolution(
+        &self,
+        other: &PiecewiseLinearFunction<T>,
+    ) -> Option<PiecewiseLinearFunction<T>> {
+        self.max_plus_convolution(&other.mirror().negate())
+    }
+
+    /// Computes the max-plus deconvolution of `self` and `other`, that is, the function
+    /// (f ⊖ g)(t) = inf { f(t + u) - g(u) }, where the infimum is taken over all `u` such that both
+    /// `t + u` and `u` are in the domains of f and g respectively.
+    ///
+    /// If f and g have domains [a1, b1] and [a2, b2], the resulting function has domain
+    /// [a1 - b2, b1 - a2]. Returns `None` if the result is -inf somewhere.
+    ///
+    /// This is computed as f ⊗ ǧ, where ǧ(v) = -g(-v); see `min_plus_convolution()` for details
+    /// on the complexity.
+    pub fn max_plus_deconvolution(
+        &self,
+        other: &PiecewiseLinearFunction<T>,
+    ) -> Option<PiecewiseLinearFunction<T>> {
+        self.min_plus_convolution(&other.mirror().negate())
+    }
+}
+
+/// Computes the infimal convolution (or epi-sum) of several functions, that is, the function
+/// (f1 □ ... □ fk)(x) = inf { f1(y1) + ... + fk(yk) }, where the infimum is taken over all `y1`,
+/// ..., `yk` which sum to `x` and are in the domains of the respective functions.
+///
+/// If all functions are convex, the result is obtained by merging their segments in order of
+/// increasing slope, and their domains may be infinite. When several functions extend to -inf
+/// (resp. +inf), the slope of the result at -inf (resp. +inf) is the greatest (resp. smallest)
+/// of their edge slopes, and the segments outside of these slopes do not contribute to the
+/// result. This is, for instance, how the supply curve of a set of generators is aggregated from
+/// their convex cost curves.
+///
+/// Otherwise, the functions are combined with `min_plus_convolution()`.
+///
+/// Returns `None` if `funcs` is empty, or if the result is -inf somewhere (which happens for
+/// convex functions when a slope at -inf is greater than a slope at +inf).
+///
+/// ## Example
+///
+/// ```
+/// use piecewise_linear::{infimal_convolution, PiecewiseLinearFunction};
+/// use std::convert::TryFrom;
+/// // Cost of producing a quantity with two generators.
+/// let f = PiecewiseLinearFunction::try_from(vec![(0., 0.), (10., 20.), (20., 60.)]).unwrap();
+/// let g = PiecewiseLinearFunction::try_from(vec![(0., 0.), (15., 45.)]).unwrap();
+/// assert_eq!(
+///     infimal_convolution(&[f, g]).unwrap(),
+///     PiecewiseLinearFunction::try_from(vec![(0., 0.), (10., 20.), (25., 65.), (35., 105.)])
+///         .unwrap()
+/// );
+/// ```
+///
+/// ## Complexity
+///
+/// For convex functions, the complexity of this method is _O(k n)_, where _k_ is the number of
+/// functions passed, and _n_ is the total number of points in the functions.
+pub fn infimal_convolution<T: CoordFloat>(
+    funcs: &[PiecewiseLinearFunction<T>],
+) -> Option<PiecewiseLinearFunction<T>> {
+    let (first, rest) = funcs.split_first()?;
+    if funcs.iter().all(|f| f.is_convex()) {
+        convex_infimal_convolution(&funcs.iter().collect::<Vec<_>>())
+    } else {
+        rest.iter()
+            .try_fold(first.clone(), |acc, f| acc.min_plus_convolution(f))
+    }
+}
+
+/// Computes the infimal convolution of convex functions by merging their segments in order of
+/// increasing slope.
+fn convex_infimal_convolution<T: CoordFloat>(
+    funcs: &[&PiecewiseLinearFunction<T>],
+) -> Option<PiecewiseLinearFunction<T>> {
+    let left_slope = funcs
+        .iter()
+        .filter(|f| f.domain().0.is_infinite())
+        .map(|f| f.edge_slopes().0)
+        .fold(T::neg_infinity(), T::max);
+    let right_slope = funcs
+        .iter()
+        .filter(|f| f.domain().1.is_infinite())
+        .map(|f| f.edge_slopes().1)
+        .fold(T::infinity(), T::min);
+    if left_slope > right_slope {
+        return None;
+    }
+
+    // The result goes through the sum of the points of each function where the slope goes above
+    // `left_slope`, and then follows the segments with slopes below `right_slope`.
+    let mut start = Coord {
+        x: T::zero(),
+        y: T::zero(),
+    };
+    let mut pieces: Vec<Vec<Line<T>>> = Vec::with_capacity(funcs.len());
+    for f in funcs {
+        let finite: Vec<Line<T>> = f
+            .segments_iter()
+            .filter(|s| s.start.x.is_finite() && s.end.x.is_finite())
+            .collect();
+        let skipped = finite
+            .iter()
+            .take_while(|s| s.slope() <= left_slope)
+            .count();
+        start = start
+            + finite.get(skipped).map_or_else(
+                || {
+                    *f.coordinates
+                        .iter()
+                        .rev()
+                        .find(|c| c.x.is_finite())
+                        .unwrap()
+                },
+                |s| s.start,
+            );
+        pieces.push(
+            finite[skipped..]
+                .iter()
+                .take_while(|s| s.slope() < right_slope)
+                .cloned()
+                .collect(),
+        );
+    }
+
+    let mut coordinates = Vec::new();
+    if left_slope.is_finite() {
+        coordinates.push(Coord {
+            x: T::neg_infinity(),
+            y: left_slope,
+        });
+    }
+    coordinates.push(start);
+    let mut next = vec![0; pieces.len()];
+    loop {
+        let steepest = (0..pieces.len())
+            .filter(|&i| next[i] < pieces[i].len())
+            .min_by(|&i, &j| {
+                bogus_compare(&pieces[i][next[i]].slope(), &pieces[j][next[j]].slope())
+            });
+        match steepest {
+            Some(i) => {
+                let last = *coordinates.last().unwrap();
+                coordinates.push(last + pieces[i][next[i]].delta());
+                next[i] += 1;
+            }
+            None => break,
+        }
+    }
+    if right_slope.is_finite() {
+        coordinates.push(Coord {
+            x: T::infinity(),
+            y: right_slope,
+        });
+    } else if coordinates.len() > 1 {
+        // Avoid accumulating rounding errors on the last point.
+        *coordinates.last_mut().unwrap() =
+            funcs.iter().map(|f| *f.coordinates.last().unwrap()).fold(
+                Coord {
+                    x: T::zero(),
+                    y: T::zero(),
+                },
+                |a, b| a + b,
+            );
+    }
+    PiecewiseLinearFunction::new(remove_collinear_points(coordinates))
+}
+
+/// Computes the min-plus or max-plus convolution of `f` and `g`, depending on `extremum`.
+fn convolution<T: CoordFloat>(
+    f: &PiecewiseLinearFunction<T>,
+    g: &PiecewiseLinearFunction<T>,
+    extremum: Extremum,
+) -> Option<PiecewiseLinearFunction<T>> {
+    if !(f.is_bounded() && g.is_bounded()) {
+        return unbounded_convolution(f, g, extremum);
+    }
+    let edge_order = extremum.edge_slope_order();
+    let merge_order = edge_order.reverse();
+    if has_sorted_slopes(f, merge_order) && has_sorted_slopes(g, merge_order) {
+        return Some(merged_convolution(f, g, merge_order));
+    }
+
+    let mut envelope = Envelope::new(extremum);
+    if has_sorted_slopes(f, edge_order) && has_sorted_slopes(g, edge_order) {
+        for &(f, g) in &[(f, g), (g, f)] {
+            for &edge in &[f.coordinates[0], *f.coordinates.last().unwrap()] {
+                envelope.insert_function(&g.translate(edge.x, edge.y));
+            }
+        }
+    } else {
+        for f_segment in f.segments_iter() {
+            for g_segment in g.segments_iter() {
+                let (first, second) = segment_convolution(&f_segment, &g_segment, merge_order);
+                envelope.insert(first);
+                envelope.insert(second);
+            }
+        }
+    }
+    envelope.into_function()
+}
+
+/// Computes the min-plus or max-plus convolution of `f` and `g` when either domain is infinite.
+///
+/// Functions whose slopes are sorted in the merge order are merged by
+/// `convex_infimal_convolution()`. Otherwise, the result is the envelope of the convolutions of
+/// all pairs of pieces, some of which are rays. Rays are cut beyond all the points where the
+/// envelope may change, and the cut is then replaced by the slope of the best ray.
+fn unbounded_convolution<T: CoordFloat>(
+    f: &PiecewiseLinearFunction<T>,
+    g: &PiecewiseLinearFunction<T>,
+    extremum: Extremum,
+) -> Option<PiecewiseLinearFunction<T>> {
+    let merge_order = extremum.edge_slope_order().reverse();
+    if has_sorted_slopes(f, merge_order) && has_sorted_slopes(g, merge_order) {
+        return match extremum {
+            Extremum::Lower => convex_infimal_convolution(&[f, g]),
+            Extremum::Upper => Some(negated(&convex_infimal_convolution(&[
+                &negated(f),
+                &negated(g),
+            ])?)),
+        };
+    }
+
+    let mut pieces = Vec::new();
+    for f_piece in f.segments_iter() {
+        for g_piece in g.segments_iter() {
+            pieces.extend(piece_convolution(&f_piece, &g_piece, merge_order)?);
+        }
+    }
+    let finite_xs = pieces
+        .iter()
+        .flat_map(|p| [p.start.x, p.end.x])
+        .filter(|x| x.is_finite());
+    let (mut lo, mut hi) = finite_xs.fold((T::infinity(), T::neg_infinity()), |(lo, hi), x| {
+        (lo.min(x), hi.max(x))
+    });
+    // Rays are represented by their finite endpoint and their slope.
+    let rays = |to_right: bool| -> Vec<(Coord<T>, T)> {
+        pieces
+            .iter()
+            .filter(|p| if to_right { p.end.x } else { p.start.x }.is_infinite())
+            .map(|p| (finite_endpoint(p), segment_slope(p)))
+            .collect()
+    };
+    let (left_rays, right_rays) = (rays(false), rays(true));
+    for rays in &[&right_rays, &left_rays] {
+        for (i, &(a, a_slope)) in rays.iter().enumerate() {
+            for &(b, b_slope) in &rays[i + 1..] {
+                if a_slope != b_slope {
+                    let x = (b.y - b_slope * b.x - a.y + a_slope * a.x) / (a_slope - b_slope);
+                    lo = lo.min(x);
+                    hi = hi.max(x);
+                }
+            }
+        }
+    }
+    // Beyond the cuts, the envelope follows a single ray on each side.
+    let margin = T::one().max(hi - lo);
+    let cuts = (lo - margin, hi + margin);
+    let ray_at =
+        |(point, slope): (Coord<T>, T), x: T| Coord::from((x, point.y + slope * (x - point.x)));
+
+    let mut envelope = Envelope::new(extremum);
+    for piece in &pieces {
+        let ray = (finite_endpoint(piece), segment_slope(piece));
+        let start = if piece.start.x.is_infinite() {
+            ray_at(ray, cuts.0)
+        } else {
+            piece.start
+        };
+        let end = if piece.end.x.is_infinite() {
+            ray_at(ray, cuts.1)
+        } else {
+            piece.end
+        };
+        envelope.insert(Line::new(start, end));
+    }
+    let mut coordinates = envelope.into_function()?.coordinates;
+    if coordinates.len() == 2 && !left_rays.is_empty() && !right_rays.is_empty() {
+        // The result is a single line, which needs to keep a finite point.
+        let line = Line::new(coordinates[0], coordinates[1]);
+        coordinates.insert(1, Coord::from((lo, y_at_x(&line, lo))));
+    }
+    let best_slope = |rays: &[(Coord<T>, T)], x: T| {
+        rays.iter()
+            .map(|&ray| (ray_at(ray, x).y, ray.1))
+            .fold(None, |best: Option<(T, T)>, (y, slope)| match best {
+                Some((best_y, _)) if extremum.prefers(best_y, y) => best,
+                _ => Some((y, slope)),
+            })
+            .map(|(_, slope)| slope)
+    };
+    if let Some(slope) = best_slope(&left_rays, cuts.0) {
+        coordinates[0] = Coord::from((T::neg_infinity(), slope));
+    }
+    if let Some(slope) = best_slope(&right_rays, cuts.1) {
+        // This unwrap is guaranteed to succeed as the envelope is not empty.
+        *coordinates.last_mut().unwrap() = Coord::from((T::infinity(), slope));
+    }
+    PiecewiseLinearFunction::new(remove_collinear_points(coordinates))
+}
+
+/// Returns the function x -> -f(x), which unlike `negate()` does not require signed numbers.
+fn negated<T: CoordFloat>(f: &PiecewiseLinearFunction<T>) -> PiecewiseLinearFunction<T> {
+    PiecewiseLinearFunction {
+        coordinates: f
+            .coordinates
+            .iter()
+            .map(|c| Coord::from((c.x, -c.y)))
+            .collect(),
+    }
+}
+
+/// Computes the convolution of two pieces of functions, which may extend to infinity (with the
+/// slope stored in their infinite endpoint), as the pieces sorted by slope in `order`.
+///
+/// Returns `None` if the result is infinite, which happens when the first piece extends to +inf
+/// and the second one to -inf.
+fn piece_convolution<T: CoordFloat>(
+    a: &Line<T>,
+    b: &Line<T>,
+    order: Ordering,
+) -> Option<Vec<Line<T>>> {
+    let (mut first, mut second) =
+        if segment_slope(a).partial_cmp(&segment_slope(b)) != Some(order.reverse()) {
+            (a, b)
+        } else {
+            (b, a)
+        };
+    if first.end.x.is_infinite() && second.start.x.is_infinite() {
+        // Pieces with the same slope can be taken in either order.
+        if segment_slope(first) != segment_slope(second) {
+            return None;
+        }
+        std::mem::swap(&mut first, &mut second);
+    }
+    // Each piece is translated by the inner endpoint of the other one. If that endpoint is
+    // infinite, the other piece is never reached.
+    let mut pieces = Vec::with_capacity(2);
+    if second.start.x.is_finite() {
+        pieces.push(translate_piece(first, second.start));
+    }
+    if first.end.x.is_finite() {
+        pieces.push(translate_piece(second, first.end));
+    }
+    Some(pieces)
+}
+
+/// Translates a piece by the finite vector `by`, keeping the slopes stored at infinite endpoints.
+fn translate_piece<T: CoordFloat>(piece: &Line<T>, by: Coord<T>) -> Line<T> {
+    let translate = |c: Coord<T>| {
+        if c.x.is_finite() {
+            c + by
+        } else {
+            c
+        }
+    };
+    Line::new(translate(piece.start), translate(piece.end))
+}
+
+/// Computes the convolution of two functions whose slopes are sorted in `order`, by merging their
+/// segments in that order.
+///
+/// This is the min-plus convolution of two convex functions for `Ordering::Less`, and the
+/// max-plus convolution of two concave functions for `Ordering::Greater`.
+fn merged_convolution<T: CoordFloat>(
+    f: &PiecewiseLinearFunction<T>,
+    g: &PiecewiseLinearFunction<T>,
+    order: Ordering,
+) -> PiecewiseLinearFunction<T> {
+    let mut f_segments = f.segments_iter().peekable();
+    let mut g_segments = g.segments_iter().peekable();
+    let mut coordinates = vec![f.coordinates[0] + g.coordinates[0]];
+    loop {
+        let next = match (f_segments.peek(), g_segments.peek()) {
+            (Some(fs), Some(gs))
+                if fs.slope().partial_cmp(&gs.slope()) != Some(order.reverse()) =>
+            {
+                f_segments.next()
+            }
+            (Some(_), Some(_)) => g_segments.next(),
+            (Some(_), None) => f_segments.next(),
+            (None, _) => g_segments.next(),
+        };
+        match next {
+            Some(segment) => {
+                let last = *coordinates.last().unwrap();
+                coordinates.push(last + segment.delta());
+            }
+            None => break,
+        }
+    }
+    // Avoid accumulating rounding errors on the last point.
+    *coordinates.last_mut().unwrap() =
+        *f.coordinates.last().unwrap() + *g.coordinates.last().unwrap();
+    PiecewiseLinearFunction::new(remove_collinear_points(coordinates)).unwrap()
+}
+
+/// Computes the convolution of two segments, which is made up of both segments, sorted by slope
+/// in `order`.
+fn segment_convolution<T: CoordFloat>(
+    a: &Line<T>,
+    b: &Line<T>,
+    order: Ordering,
+) -> (Line<T>, Line<T>) {
+    let first = if a.slope().partial_cmp(&b.slope()) != Some(order.reverse()) {
+        a
+    } else {
+        b
+    };
+    let start = a.start + b.start;
+    let middle = start + first.delta();
+    let end = a.end + b.end;
+    (Line::new(start, middle), Line::new(middle, end))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use super::*;
+    use crate::network_calculus;
+
+    /// Computes the min-plus or max-plus convolution at `t` by evaluating all values of `s` where
+    /// the extremum may be reached.
+    fn brute_force_convolution(
+        f: &PiecewiseLinearFunction<f64>,
+        g: &PiecewiseLinearFunction<f64>,
+        t: f64,
+        extremum: Extremum,
+    ) -> f64 {
+        let (lo, hi) = (
+            f.domain().0.max(t - g.domain().1),
+            f.domain().1.min(t - g.domain().0),
+        );
+        let values = f
+            .coordinates
+            .iter()
+            .map(|c| c.x)
+            .chain(g.coordinates.iter().map(|c| t - c.x))
+            .chain(vec![lo, hi])
+            .filter(|&s| s.is_finite() && s >= lo && s <= hi)
+            .map(|s| f.y_at_x_clamped(s) + g.y_at_x_clamped(t - s));
+        match extremum {
+            Extremum::Lower => values.fold(f64::INFINITY, f64::min),
+            Extremum::Upper => values.fold(f64::NEG_INFINITY, f64::max),
+        }
+    }
+
+    /// Checks both convolutions of `f` and `g` against a brute force computation, over [-10, 10]
+    /// for infinite domains.
+    fn check_convolutions(f: &PiecewiseLinearFunction<f64>, g: &PiecewiseLinearFunction<f64>) {
+        for &extremum in &[Extremum::Lower, Extremum::Upper] {
+            let h = match extremum {
+                Extremum::Lower => f.min_plus_convolution(g).unwrap(),
+                Extremum::Upper => f.max_plus_convolution(g).unwrap(),
+            };
+            let (start, end) = h.domain();
+            assert_eq!(start, f.domain().0 + g.domain().0);
+            assert_eq!(end, f.domain().1 + g.domain().1);
+            let (start, end) = (start.max(-10.), end.min(10.));
+            for i in 0..=100 {
+                let t = start + (end - start) * f64::from(i) / 100.;
+                let expected = brute_force_convolution(f, g, t, extremum);
+                assert!(
+                    (h.y_at_x(t).unwrap() - expected).abs() < 1e-9,
+                    "{:?} at {}",
+                    extremum,
+                    t
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_convex_convolution() {
+        let f = PiecewiseLinearFunction::try_from(vec![(0., 0.), (1., 1.), (2., 3.)]).unwrap();
+        let g = PiecewiseLinearFunction::try_from(vec![(0., 0.), (1., 0.5), (3., 2.5)]).unwrap();
+        assert_eq!(
+            f.min_plus_convolution(&g).unwrap(),
+            PiecewiseLinearFunction::try_from(vec![(0., 0.), (1., 0.5), (4., 3.5), (5., 5.5)])
+                .unwrap()
+        );
+        check_convolutions(&f, &g);
+    }
+
+    #[test]
+    fn test_concave_convolution() {
+        let f = PiecewiseLinearFunction::try_from(vec![(0., 0.), (1., 2.), (2., 3.)]).unwrap();
+        let g = PiecewiseLinearFunction::try_from(vec![(-1., 1.), (2., 4.), (3., 4.5)]).unwrap();
+        assert_eq!(
+            f.max_plus_convolution(&g).unwrap(),
+            PiecewiseLinearFunction::try_from(vec![(-1., 1.), (0., 3.), (4., 7.), (5., 7.5)])
+                .unwrap()
+        );
+        check_convolutions(&f, &g);
+        check_convolutions(&g, &f);
+    }
+
+    #[test]
+    fn test_general_convolution() {
+        let f = PiecewiseLinearFunction::try_from(vec![
+            (0., 1.),
+            (1., 3.),
+            (2., 0.5),
+            (4., 2.),
+            (5., -1.),
+        ])
+        .unwrap();
+        let g = PiecewiseLinearFunction::try_from(vec![(0., 0.), (0.5, 2.), (2., 1.), (3., 4.)])
+            .unwrap();
+        check_convolutions(&f, &g);
+        check_convolutions(&g, &f);
+        check_convolutions(&f, &f);
+
+        let unbounded = PiecewiseLinearFunction::constant((0., f64::INFINITY), 1.).unwrap();
+        check_convolutions(&f, &unbounded);
+        check_convolutions(&unbounded, &g);
+    }
+
+    #[test]
+    fn test_unbounded_convolution() {
+        // A token bucket arrival curve and a rate-latency service curve.
+        let alpha = network_calculus::token_bucket(1., 2.).unwrap();
+        let beta = network_calculus::rate_latency(3., 2.).unwrap();
+        let expected =
+            PiecewiseLinearFunction::try_from(vec![(0., 2.), (2., 2.), (f64::INFINITY, 1.)])
+                .unwrap();
+        assert_eq!(alpha.min_plus_convolution(&beta).unwrap(), expected);
+        assert_eq!(beta.min_plus_convolution(&alpha).unwrap(), expected);
+        check_convolutions(&alpha, &beta);
+
+        let wavy = PiecewiseLinearFunction::with_edge_slopes(
+            vec![(0., 0.).into(), (1., 3.).into(), (2., 1.).into()],
+            None,
+            Some(0.5),
+        )
+        .unwrap();
+        check_convolutions(&wavy, &alpha);
+        check_convolutions(&wavy, &beta);
+        check_convolutions(&wavy, &wavy);
+        let left = PiecewiseLinearFunction::with_edge_slopes(
+            vec![(-1., 1.).into(), (0., 0.).into(), (1., 2.).into()],
+            Some(-2.),
+            None,
+        )
+        .unwrap();
+        let g = PiecewiseLinearFunction::try_from(vec![(0., 0.), (0.5, 2.), (2., 1.)]).unwrap();
+        check_convolutions(&left, &g);
+        check_convolutions(&g, &left);
+        check_convolutions(&left, &left);
+
+        // f(s) + g(t - s) = 2t - s for all s >= max(0, t), which has no lower bound.
+        let falling =
+            PiecewiseLinearFunction::with_edge_slopes(vec![(0., 0.).into()], Some(2.), None)
+                .unwrap();
+        let rising = network_calculus::token_bucket(1., 0.).unwrap();
+        assert_eq!(rising.min_plus_convolution(&falling), None);
+        assert_eq!(
+            rising.max_plus_convolution(&falling).unwrap(),
+            PiecewiseLinearFunction::with_edge_slopes(vec![(0., 0.).into()], Some(2.), Some(1.))
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_infimal_convolution() {
+        let f = PiecewiseLinearFunction::try_from(vec![(0., 0.), (1., 1.), (2., 3.)]).unwrap();
+        let g = PiecewiseLinearFunction::try_from(vec![(0., 0.), (1., 0.5), (3., 2.5)]).unwrap();
+        let h = PiecewiseLinearFunction::try_from(vec![(-1., 2.), (0., 0.), (2., 0.)]).unwrap();
+        assert_eq!(f.infimal_convolution(&g), f.min_plus_convolution(&g));
+        let all = infimal_convolution(&[f.clone(), g.clone(), h.clone()]).unwrap();
+        let pairwise = f
+            .min_plus_convolution(&g)
+            .unwrap()
+            .min_plus_convolution(&h)
+            .unwrap();
+        assert_eq!(all.domain(), pairwise.domain());
+        for i in 0..=70 {
+            let x = -1. + f64::from(i) / 10.;
+            assert!((all.y_at_x(x).unwrap() - pairwise.y_at_x(x).unwrap()).abs() < 1e-12);
+        }
+        assert_eq!(infimal_convolution(std::slice::from_ref(&f)).unwrap(), f);
+        assert_eq!(infimal_convolution::<f64>(&[]), None);
+
+        let general =
+            PiecewiseLinearFunction::try_from(vec![(0., 1.), (1., 3.), (2., 0.5)]).unwrap();
+        assert_eq!(
+            infimal_convolution(&[general.clone(), f.clone()]),
+            general.min_plus_convolution(&f)
+        );
+    }
+
+    #[test]
+    fn test_infimal_convolution_infinite_domains() {
+        let abs =
+            PiecewiseLinearFunction::with_edge_slopes(vec![(0., 0.).into()], Some(-1.), Some(1.))
+                .unwrap();
+        let steep = PiecewiseLinearFunction::try_from(vec![(-1., 2.), (0., 0.), (1., 2.)]).unwrap();
+        assert_eq!(abs.infimal_convolution(&steep).unwrap(), abs);
+
+        let f = PiecewiseLinearFunction::with_edge_slopes(
+            vec![(0., 0.).into(), (1., 0.).into()],
+            Some(-1.),
+            None,
+        )
+        .unwrap();
+        let g = PiecewiseLinearFunction::try_from(vec![(0., 1.), (2., 3.)]).unwrap();
+        assert_eq!(
+            f.infimal_convolution(&g).unwrap(),
+            PiecewiseLinearFunction::try_from(vec![
+                (f64::NEG_INFINITY, -1.),
+                (0., 1.),
+                (1., 1.),
+                (3., 3.)
+            ])
+            .unwrap()
+        );
+
+        let rising =
+            PiecewiseLinearFunction::with_edge_slopes(vec![(0., 0.).into()], Some(2.), None)
+                .unwrap();
+        assert_eq!(abs.infimal_convolution(&rising), None);
+        assert_eq!(abs.min_plus_convolution(&steep).unwrap(), abs);
+    }
+
+    #[test]
+    fn test_deconvolutions() {
+        let f = PiecewiseLinearFunction::try_from(vec![(0., 1.), (1., 3.), (2., 0.5), (4., 2.)])
+            .unwrap();
+        let g = PiecewiseLinearFunction::try_from(vec![(0., 0.), (1., 1.), (3., 1.5)]).unwrap();
+        let min_plus = f.min_plus_deconvolution(&g).unwrap();
+        let max_plus = f.max_plus_deconvolution(&g).unwrap();
+        assert_eq!(min_plus.domain(), (-3., 4.));
+        assert_eq!(max_plus.domain(), (-3., 4.));
+        for i in 0..=70 {
+            let t = -3. + f64::from(i) / 10.;
+            let (lo, hi) = (0f64.max(-t), 3f64.min(4. - t));
+            let values = g
+                .coordinates
+                .iter()
+                .map(|c| c.x)
+                .chain(f.coordinates.iter().map(|c| c.x - t))
+                .chain(vec![lo, hi])
+                .filter(|&u| u >= lo && u <= hi)
+                .map(|u| f.y_at_x(t + u).unwrap() - g.y_at_x(u).unwrap())
+                .collect::<Vec<_>>();
+            let sup = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+            let inf = values.iter().cloned().fold(f64::INFINITY, f64::min);
+            assert!((min_plus.y_at_x(t).unwrap() - sup).abs() < 1e-9, "at {}", t);
+            assert!((max_plus.y_at_x(t).unwrap() - inf).abs() < 1e-9, "at {}", t);
+        }
+    }
+}
@@ -0,0 +1,296 @@
+// Copyright 2019 Matthieu Felix
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Reading and writing functions as CSV.
+//!
+//! A single function is stored as two columns, `x` and `y`. Several functions with the same domain
+//! are stored as one `x` column followed by one `y` column per function, with a row for each point
+//! of inflection of any of the functions.
+//!
+//! Fields are not quoted, and blank lines are ignored.
+
+use std::fmt::Display;
+use std::io::{BufRead, Write};
+
+use crate::{points_of_inflection_iter, Coord, CoordFloat, Error, PiecewiseLinearFunction};
+
+/// Options for reading and writing CSV files.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CsvOptions {
+    /// The character separating fields.
+    pub delimiter: char,
+    /// Whether the first line is a header. It is skipped when reading, and written as
+    /// `x,y` (or `x,y1,y2,...` for several functions) when writing.
+    pub has_header: bool,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        CsvOptions {
+            delimiter: ',',
+            has_header: true,
+        }
+    }
+}
+
+impl<T: CoordFloat> PiecewiseLinearFunction<T> {
+    /// Reads a function from a CSV file with two columns, `x` and `y`.
+    ///
+    /// Returns `Error::Parse` with the number of the offending line if a line does not have two
+    /// fields, if a field is not a number, or if the `x` values are not finite and increasing, and
+    /// `Error::TooFewPoints` if there are less than two points.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use piecewise_linear::{CsvOptions, PiecewiseLinearFunction};
+    /// let csv = "time;power\n0;1.5\n2;3\n";
+    /// let options = CsvOptions { delimiter: ';', has_header: true };
+    /// let f = PiecewiseLinearFunction::<f64>::read_csv(csv.as_bytes(), &options).unwrap();
+    /// assert_eq!(f.y_at_x(1.), Some(2.25));
+    /// ```
+    pub fn read_csv<R: BufRead>(reader: R, options: &CsvOptions) -> Result<Self, Error> {
+        let mut functions = read_rows(reader, options, Some(1))?;
+        Ok(functions.pop().unwrap())
+    }
+}
+
+impl<T: CoordFloat + Display> PiecewiseLinearFunction<T> {
+    /// Writes this function as a CSV file with two columns, `x` and `y`.
+    pub fn write_csv<W: Write>(&self, writer: &mut W, options: &CsvOptions) -> Result<(), Error> {
+        if options.has_header {
+            writeln!(writer, "x{}y", options.delimiter)?;
+        }
+        for c in &self.coordinates {
+            writeln!(writer, "{}{}{}", c.x, options.delimiter, c.y)?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads several functions with the same domain from a CSV file with one `x` column and one `y`
+/// column per function.
+///
+/// Returns `Error::Parse` with the number of the offending line if lines do not all have the
+/// same number of fields, if a field is not a number, or if the `x` values are not finite and
+/// increasing, and `Error::TooFewPoints` if there are less than two points.
+pub fn read_csv_functions<T: CoordFloat, R: BufRead>(
+    reader: R,
+    options: &CsvOptions,
+) -> Result<Vec<PiecewiseLinearFunction<T>>, Error> {
+    read_rows(reader, options, None)
+}
+
+/// Writes several functions with the same domain as a CSV file with one `x` column and one `y`
+/// column per function. There is one row for each point of inflection of any of the functions.
+///
+/// Returns `Error::DomainMismatch` if the functions do not all have the same domain, or if there
+/// are none.
+///
+/// ## Example
+///
+/// ```
+/// use piecewise_linear::{write_csv_functions, CsvOptions, PiecewiseLinearFunction};
+/// use std::convert::TryFrom;
+/// let f = PiecewiseLinearFunction::try_from(vec![(0., 0.), (2., 2.)]).unwrap();
+/// let g = PiecewiseLinearFunction::try_from(vec![(0., 1.), (1., 0.), (2., 1.)]).unwrap();
+/// let mut csv = Vec::new();
+/// write_csv_functions(&[f, g], &mut csv, &CsvOptions::default()).unwrap();
+/// assert_eq!(String::from_utf8(csv).unwrap(), "x,y1,y2\n0,0,1\n1,1,0\n2,2,1\n");
+/// ```
+pub fn write_csv_functions<T: CoordFloat + Display, W: Write>(
+    funcs: &[PiecewiseLinearFunction<T>],
+    writer: &mut W,
+    options: &CsvOptions,
+) -> Result<(), Error> {
+    let rows = points_of_inflection_iter(funcs).ok_or(Error::DomainMismatch)?;
+    let delimiter = options.delimiter;
+    if options.has_header {
+        write!(writer, "x")?;
+        for i in 1..=funcs.len() {
+            write!(writer, "{}y{}", delimiter, i)?;
+        }
+        writeln!(writer)?;
+    }
+    for (x, values) in rows {
+        write!(writer, "{}", x)?;
+        for y in values {
+            write!(writer, "{}{}", delimiter, y)?;
+        }
+        writeln!(writer)?;
+    }
+    Ok(())
+}
+
+/// Reads rows of an `x` value followed by `y` values into one function per `y` column. If
+/// `y_columns` is `None`, the number of columns is set by the first row.
+fn read_rows<T: CoordFloat, R: BufRead>(
+    reader: R,
+    options: &CsvOptions,
+    y_columns: Option<usize>,
+) -> Result<Vec<PiecewiseLinearFunction<T>>, Error> {
+    let mut y_columns = y_columns;
+    let mut columns: Vec<Vec<Coord<T>>> = Vec::new();
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line_number = index + 1;
+        if (options.has_header && index == 0) || line.trim().is_empty() {
+            continue;
+        }
+        let parse_error = |message: String| Error::Parse {
+            line: line_number,
+            message,
+        };
+
+        let fields = line
+            .split(options.delimiter)
+            .map(|field| {
+                let field = field.trim();
+                T::from_str_radix(field, 10)
+                    .map_err(|_| parse_error(format!("`{}` is not a number", field)))
+            })
+            .collect::<Result<Vec<T>, Error>>()?;
+        // There is at least one `y` column.
+        let expected = 1 + *y_columns.get_or_insert(fields.len().saturating_sub(1).max(1));
+        if fields.len() != expected {
+            return Err(parse_error(format!(
+                "expected {} fields, found {}",
+                expected,
+                fields.len()
+            )));
+        }
+        if columns.is_empty() {
+            columns = vec![Vec::new(); expected - 1];
+        }
+        let x = fields[0];
+        if !x.is_finite() {
+            return Err(parse_error("x values must be finite".to_string()));
+        }
+        if let Some(previous) = columns[0].last() {
+            if previous.x >= x {
+                return Err(parse_error(
+                    "x values must be strictly increasing".to_string(),
+                ));
+            }
+        }
+        for (column, &y) in columns.iter_mut().zip(&fields[1..]) {
+            column.push(Coord { x, y });
+        }
+    }
+    if columns.is_empty() {
+        return Err(Error::TooFewPoints);
+    }
+    columns
+        .into_iter()
+        .map(PiecewiseLinearFunction::try_new)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let f =
+            PiecewiseLinearFunction::try_from(vec![(-1.5, 0.1), (0., 1e-20), (3., -7.25)]).unwrap();
+        for &options in &[
+            CsvOptions::default(),
+            CsvOptions {
+                delimiter: '\t',
+                has_header: false,
+            },
+        ] {
+            let mut csv = Vec::new();
+            f.write_csv(&mut csv, &options).unwrap();
+            assert_eq!(
+                PiecewiseLinearFunction::read_csv(csv.as_slice(), &options).unwrap(),
+                f
+            );
+        }
+
+        let g = PiecewiseLinearFunction::try_from(vec![(-1.5, 2.), (3., 2.)]).unwrap();
+        let mut csv = Vec::new();
+        write_csv_functions(&[f.clone(), g.clone()], &mut csv, &CsvOptions::default()).unwrap();
+        let functions = read_csv_functions(csv.as_slice(), &CsvOptions::default()).unwrap();
+        assert_eq!(functions[0], f);
+        // `g` gets the points of inflection of `f`.
+        assert_eq!(functions[1].domain(), g.domain());
+        assert_eq!(functions[1].y_at_x(0.), Some(2.));
+
+        let shorter = PiecewiseLinearFunction::try_from(vec![(0., 2.), (3., 2.)]).unwrap();
+        assert_eq!(
+            write_csv_functions(&[f, shorter], &mut Vec::new(), &CsvOptions::default()),
+            Err(Error::DomainMismatch)
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let read = |csv: &str| {
+            PiecewiseLinearFunction::<f64>::read_csv(csv.as_bytes(), &CsvOptions::default())
+        };
+        assert_eq!(
+            read("x,y\n0,1\n\n1,abc\n"),
+            Err(Error::Parse {
+                line: 4,
+                message: "`abc` is not a number".to_string()
+            })
+        );
+        assert_eq!(
+            read("x,y\n0,1\n1,2,3\n"),
+            Err(Error::Parse {
+                line: 3,
+                message: "expected 2 fields, found 3".to_string()
+            })
+        );
+        assert_eq!(
+            read("x,y\n0,1\n2,2\n1,3\n"),
+            Err(Error::Parse {
+                line: 4,
+                message: "x values must be strictly increasing".to_string()
+            })
+        );
+        assert_eq!(
+            read("x,y\ninf,1\n1,2\n"),
+            Err(Error::Parse {
+                line: 2,
+                message: "x values must be finite".to_string()
+            })
+        );
+        assert_eq!(
+            read("x,y\n0,1\nNaN,2\n"),
+            Err(Error::Parse {
+                line: 3,
+                message: "x values must be finite".to_string()
+            })
+        );
+        assert_eq!(read("x,y\n0,1\n"), Err(Error::TooFewPoints));
+        assert_eq!(read("x,y\n"), Err(Error::TooFewPoints));
+
+        let multiple = read_csv_functions::<f64, _>(
+            "0,1,2\n1,2\n".as_bytes(),
+            &CsvOptions {
+                delimiter: ',',
+                has_header: false,
+            },
+        );
+        assert_eq!(
+            multiple.unwrap_err().to_string(),
+            "line 2: expected 3 fields, found 2"
+        );
+    }
+}
@@ -0,0 +1,559 @@
+// Copyright 2019 Matthieu Felix
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Continuous probability distributions with a piecewise linear density.
+//!
+//! The cumulative distribution function of such a distribution is piecewise quadratic, and its
+//! moments and quantiles are computed exactly. With the `rand` feature, distributions can be
+//! sampled by inverse transform with any `rand::Rng`.
+//!
+//! Cumulative distribution functions which are themselves piecewise linear, such as interpolated
+//! empirical CDFs, are represented by `PiecewiseLinearCdf`.
+//!
+//! ## Example
+//!
+//! ```
+//! use piecewise_linear::distribution::PiecewiseLinearDistribution;
+//! let triangular = PiecewiseLinearDistribution::triangular(0., 1., 4.).unwrap();
+//! assert_eq!(triangular.cdf(1.), 0.25);
+//! assert_eq!(triangular.quantile(0.25), Some(1.));
+//! assert_eq!(triangular.mean(), 5. / 3.);
+//! ```
+
+use crate::{
+    points_of_inflection_iter, Coord, CoordFloat, ExpandDomainStrategy, PiecewiseLinearFunction,
+};
+
+/// A continuous probability distribution whose density is a piecewise linear function over a
+/// bounded domain.
+#[derive(PartialEq, Clone, Debug)]
+pub struct PiecewiseLinearDistribution<T: CoordFloat> {
+    pdf: PiecewiseLinearFunction<T>,
+    /// The value of the cumulative distribution function at each point of `pdf`.
+    cdf_values: Vec<T>,
+}
+
+impl<T: CoordFloat> PiecewiseLinearDistribution<T> {
+    /// Creates a new distribution with the density `pdf`.
+    ///
+    /// Returns `None` if the domain of `pdf` is not bounded, if it is negative anywhere, or if its
+    /// integral differs from 1 by more than the square root of the machine epsilon.
+    pub fn new(pdf: PiecewiseLinearFunction<T>) -> Option<Self> {
+        let cdf_values = unnormalized_cdf_values(&pdf)?;
+        if (*cdf_values.last().unwrap() - T::one()).abs() <= T::epsilon().sqrt() {
+            Some(PiecewiseLinearDistribution { pdf, cdf_values })
+        } else {
+            None
+        }
+    }
+
+    /// Creates a new distribution whose density is proportional to `f`.
+    ///
+    /// Returns `None` if the domain of `f` is not bounded, if it is negative anywhere, or if its
+    /// integral is zero.
+    pub fn from_unnormalized(f: PiecewiseLinearFunction<T>) -> Option<Self> {
+        let mut cdf_values = unnormalized_cdf_values(&f)?;
+        let total = *cdf_values.last().unwrap();
+        if total <= T::zero() {
+            return None;
+        }
+        let pdf = PiecewiseLinearFunction::new(
+            f.coordinates
+                .iter()
+                .map(|c| Coord {
+                    x: c.x,
+                    y: c.y / total,
+                })
+                .collect(),
+        )?;
+        cdf_values.iter_mut().for_each(|c| *c = *c / total);
+        Some(PiecewiseLinearDistribution { pdf, cdf_values })
+    }
+
+    /// Returns the triangular distribution over [`min`, `max`] whose density peaks at `mode`.
+    ///
+    /// Returns `None` if the parameters are not finite, or unless `min <= mode <= max` and
+    /// `min < max`.
+    pub fn triangular(min: T, mode: T, max: T) -> Option<Self> {
+        Self::trapezoidal(min, mode, mode, max)
+    }
+
+    /// Returns the trapezoidal distribution whose density grows linearly over [`a`, `b`], is
+    /// constant over [`b`, `c`], and decreases linearly over [`c`, `d`].
+    ///
+    /// Returns `None` if the parameters are not finite, or unless `a <= b <= c <= d` and `a < d`.
+    pub fn trapezoidal(a: T, b: T, c: T, d: T) -> Option<Self> {
+        let params = [a, b, c, d];
+        if !(params.iter().all(|p| p.is_finite()) && a <= b && b <= c && c <= d && a < d) {
+            return None;
+        }
+        let height = (T::one() + T::one()) / (d + c - b - a);
+        let mut coordinates: Vec<Coord<T>> = Vec::with_capacity(4);
+        for (i, &x) in params.iter().enumerate() {
+            let y = if i == 0 || i == 3 { T::zero() } else { height };
+            match coordinates.last_mut() {
+                // Points at the same position merge into one at the top of the density.
+                Some(last) if last.x == x => last.y = height,
+                _ => coordinates.push(Coord { x, y }),
+            }
+        }
+        Self::new(PiecewiseLinearFunction::new(coordinates)?)
+    }
+
+    /// Returns the probability density function.
+    pub fn pdf(&self) -> &PiecewiseLinearFunction<T> {
+        &self.pdf
+    }
+
+    /// Returns the domain of the density, outside of which the probability is zero.
+    pub fn support(&self) -> (T, T) {
+        self.pdf.domain()
+    }
+
+    /// Computes the cumulative distribution function at `x`, that is, the probability that a
+    /// sample is at most `x`.
+    pub fn cdf(&self, x: T) -> T {
+        let (start, end) = self.support();
+        if x.is_nan() {
+            return x;
+        } else if x <= start {
+            return T::zero();
+        } else if x >= end {
+            return T::one();
+        }
+        let two = T::one() + T::one();
+        let points = &self.pdf.coordinates;
+        let i = points.partition_point(|c| c.x <= x) - 1;
+        let (p, q) = (points[i], points[i + 1]);
+        let u = x - p.x;
+        let cdf = self.cdf_values[i] + u * (p.y + u * (q.y - p.y) / ((q.x - p.x) * two));
+        cdf.min(T::one())
+    }
+
+    /// Computes the quantile function at `p`, that is, the smallest `x` such that
+    /// `cdf(x) >= p`.
+    ///
+    /// Returns `None` unless `0 <= p <= 1`.
+    pub fn quantile(&self, p: T) -> Option<T> {
+        if !(p >= T::zero() && p <= T::one()) {
+            return None;
+        }
+        let two = T::one() + T::one();
+        let points = &self.pdf.coordinates;
+        let i = self.cdf_values.partition_point(|&c| c < p);
+        if i == 0 {
+            return Some(points[0].x);
+        } else if i == points.len() {
+            return Some(points[i - 1].x);
+        }
+        // Solve a u² + b u = r over the segment [points[i - 1], points[i]].
+        let (start, end) = (points[i - 1], points[i]);
+        let width = end.x - start.x;
+        let a = (end.y - start.y) / (width * two);
+        let b = start.y;
+        let r = p - self.cdf_values[i - 1];
+        let discriminant = (b * b + two * two * a * r).max(T::zero());
+        // This form of the solution is numerically stable, and also holds when a = 0.
+        let u = two * r / (b + discriminant.sqrt());
+        Some(start.x + u.max(T::zero()).min(width))
+    }
+
+    /// Returns the mean of the distribution.
+    pub fn mean(&self) -> T {
+        // This unwrap is guaranteed to succeed as the density is bounded and integrates to 1.
+        self.pdf.weighted_mean_x().unwrap()
+    }
+
+    /// Returns the variance of the distribution.
+    pub fn variance(&self) -> T {
+        // This unwrap is guaranteed to succeed as the density is bounded and integrates to 1.
+        self.pdf.variance().unwrap()
+    }
+
+    /// Returns the standard deviation of the distribution.
+    pub fn standard_deviation(&self) -> T {
+        self.variance().sqrt()
+    }
+
+    /// Draws a sample from the distribution by inverse transform sampling.
+    #[cfg(feature = "rand")]
+    pub fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> T {
+        let p = T::from(rng.gen::<f64>()).unwrap();
+        // This unwrap is guaranteed to succeed because `p` is in [0, 1[.
+        self.quantile(p).unwrap()
+    }
+}
+
+/// A cumulative distribution function which is piecewise linear over a bounded domain, i.e. the
+/// CDF of a distribution with a piecewise constant density.
+///
+/// ## Example
+///
+/// ```
+/// use piecewise_linear::distribution::PiecewiseLinearCdf;
+/// let cdf = PiecewiseLinearCdf::from_samples(&[3., 1., 2., 5.]).unwrap();
+/// assert_eq!(cdf.cdf(2.), 1. / 3.);
+/// assert_eq!(cdf.quantile(0.5), Some(2.5));
+/// ```
+#[derive(PartialEq, Clone, Debug)]
+pub struct PiecewiseLinearCdf<T: CoordFloat> {
+    function: PiecewiseLinearFunction<T>,
+}
+
+impl<T: CoordFloat> PiecewiseLinearCdf<T> {
+    /// Creates a new CDF from its values over its domain.
+    ///
+    /// Returns `None` if the domain of `function` is not bounded, if `function` is not
+    /// non-decreasing, or unless it goes from 0 at the start of its domain to 1 at the end.
+    pub fn new(function: PiecewiseLinearFunction<T>) -> Option<Self> {
+        let first = function.coordinates[0].y;
+        let last = function.coordinates.last().unwrap().y;
+        if function.is_bounded()
+            && function.is_increasing(false)
+            && first == T::zero()
+            && last == T::one()
+        {
+            Some(PiecewiseLinearCdf { function })
+        } else {
+            None
+        }
+    }
+
+    /// Builds the CDF of the distribution of `samples`, by linear interpolation of their empirical
+    /// CDF: the k-th smallest of _n_ samples is mapped to the probability (k - 1) / (n - 1).
+    ///
+    /// Tied samples are merged into a single point at their average rank, except for the
+    /// smallest and largest samples, which are always mapped to 0 and 1. The quantile function of
+    /// the result is then the usual linear interpolation of sample quantiles (type 7 in
+    /// Hyndman and Fan's classification).
+    ///
+    /// Returns `None` if any sample is NaN, or if there are less than two distinct samples.
+    pub fn from_samples(samples: &[T]) -> Option<Self> {
+        if samples.iter().any(|s| s.is_nan()) {
+            return None;
+        }
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let n = T::from(sorted.len())?;
+        let two = T::one() + T::one();
+
+        let mut coordinates: Vec<Coord<T>> = Vec::new();
+        let mut first_rank = 0;
+        while first_rank < sorted.len() {
+            let x = sorted[first_rank];
+            let count = sorted[first_rank..].iter().take_while(|&&s| s == x).count();
+            let last_rank = first_rank + count - 1;
+            let average_rank = (T::from(first_rank)? + T::from(last_rank)?) / two;
+            coordinates.push(Coord {
+                x,
+                y: average_rank / (n - T::one()),
+            });
+            first_rank += count;
+        }
+        if coordinates.len() < 2 {
+            return None;
+        }
+        coordinates[0].y = T::zero();
+        coordinates.last_mut().unwrap().y = T::one();
+        Self::new(PiecewiseLinearFunction::new(coordinates)?)
+    }
+
+    /// Returns the weighted mixture of several distributions, whose CDF is the weighted average of
+    /// their CDFs. Weights are normalized so that they sum to 1.
+    ///
+    /// Returns `None` if there are no components, or if the weights are negative or sum to zero.
+    pub fn mixture(components: &[(T, &PiecewiseLinearCdf<T>)]) -> Option<Self> {
+        if components.iter().any(|&(w, _)| w < T::zero() || w.is_nan()) {
+            return None;
+        }
+        let total = components
+            .iter()
+            .fold(T::zero(), |acc, &(weight, _)| acc + weight);
+        if !(total > T::zero() && total.is_finite()) {
+            return None;
+        }
+        let domain = union_domain(components.iter().map(|&(_, c)| c))?;
+        let expanded: Vec<PiecewiseLinearFunction<T>> = components
+            .iter()
+            .map(|&(_, c)| c.expanded_function(domain))
+            .collect();
+        let coordinates = points_of_inflection_iter(&expanded)?
+            .map(|(x, values)| Coord {
+                x,
+                y: components
+                    .iter()
+                    .zip(values)
+                    .fold(T::zero(), |acc, (&(weight, _), y)| acc + weight * y)
+                    / total,
+            })
+            .collect::<Vec<_>>();
+        let mut function = PiecewiseLinearFunction::new(coordinates)?;
+        // Both ends are equal to 0 and 1 up to rounding errors; make them exact.
+        function.coordinates[0].y = T::zero();
+        function.coordinates.last_mut().unwrap().y = T::one();
+        Self::new(function)
+    }
+
+    /// Returns the CDF as a piecewise linear function over its domain.
+    pub fn function(&self) -> &PiecewiseLinearFunction<T> {
+        &self.function
+    }
+
+    /// Computes the probability that a sample is at most `x`.
+    pub fn cdf(&self, x: T) -> T {
+        self.function.y_at_x_clamped(x)
+    }
+
+    /// Computes the quantile function at `p`, that is, the smallest `x` such that
+    /// `cdf(x) >= p`.
+    ///
+    /// Returns `None` unless `0 <= p <= 1`.
+    pub fn quantile(&self, p: T) -> Option<T> {
+        if !(p >= T::zero() && p <= T::one()) {
+            return None;
+        }
+        let points = &self.function.coordinates;
+        let i = points.partition_point(|c| c.y < p);
+        if i == 0 {
+            return Some(points[0].x);
+        }
+        // There is always a point with the value 1, so `i` is in bounds.
+        let (start, end) = (points[i - 1], points[i]);
+        Some(start.x + (p - start.y) / (end.y - start.y) * (end.x - start.x))
+    }
+
+    /// Computes the Kolmogorov–Smirnov statistic of this CDF and `other`, that is, the greatest
+    /// absolute difference between them.
+    pub fn ks_distance(&self, other: &PiecewiseLinearCdf<T>) -> T {
+        // This unwrap is guaranteed to succeed as there are two functions.
+        let domain = union_domain(vec![self, other].into_iter()).unwrap();
+        let expanded = [
+            self.expanded_function(domain),
+            other.expanded_function(domain),
+        ];
+        // The difference between both functions is linear between their points of inflection.
+        points_of_inflection_iter(&expanded)
+            .unwrap()
+            .map(|(_, values)| (values[0] - values[1]).abs())
+            .fold(T::zero(), T::max)
+    }
+
+    /// Returns the CDF over `domain`, which must contain its domain.
+    fn expanded_function(&self, domain: (T, T)) -> PiecewiseLinearFunction<T> {
+        self.function
+            .expand_domain(domain, ExpandDomainStrategy::ExtendValue)
+    }
+}
+
+/// Returns the smallest domain containing the domains of all `cdfs`, or `None` if there are none.
+fn union_domain<'a, T: CoordFloat + 'a>(
+    cdfs: impl Iterator<Item = &'a PiecewiseLinearCdf<T>>,
+) -> Option<(T, T)> {
+    cdfs.map(|c| c.function.domain())
+        .fold(None, |acc: Option<(T, T)>, (start, end)| match acc {
+            Some((s, e)) => Some((s.min(start), e.max(end))),
+            None => Some((start, end)),
+        })
+}
+
+/// Returns the integral of `f` from the start of its domain to each of its points, or `None` if
+/// `f` is not a valid unnormalized density, i.e. if its domain is not bounded, if it is negative
+/// anywhere, or if its integral is not finite.
+fn unnormalized_cdf_values<T: CoordFloat>(f: &PiecewiseLinearFunction<T>) -> Option<Vec<T>> {
+    if !f.is_bounded()
+        || f.coordinates
+            .iter()
+            .any(|c| c.y < T::zero() || c.y.is_nan())
+    {
+        return None;
+    }
+    let two = T::one() + T::one();
+    let mut cdf_values = Vec::with_capacity(f.coordinates.len());
+    cdf_values.push(T::zero());
+    for w in f.coordinates.windows(2) {
+        let area = (w[1].x - w[0].x) * (w[0].y + w[1].y) / two;
+        cdf_values.push(*cdf_values.last().unwrap() + area);
+    }
+    if cdf_values.last().unwrap().is_finite() {
+        Some(cdf_values)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use super::*;
+
+    /// Computes the moments of the distribution numerically.
+    fn numerical_moments(d: &PiecewiseLinearDistribution<f64>) -> (f64, f64) {
+        let (start, end) = d.support();
+        let steps = 100_000;
+        let dx = (end - start) / f64::from(steps);
+        let (mut mean, mut second) = (0., 0.);
+        for i in 0..steps {
+            let x = start + (f64::from(i) + 0.5) * dx;
+            let density = d.pdf().y_at_x(x).unwrap();
+            mean += x * density * dx;
+            second += x * x * density * dx;
+        }
+        (mean, second - mean * mean)
+    }
+
+    #[test]
+    fn test_new() {
+        let half = PiecewiseLinearFunction::try_from(vec![(0., 0.5), (1., 0.5)]).unwrap();
+        assert_eq!(PiecewiseLinearDistribution::new(half.clone()), None);
+        let uniform = PiecewiseLinearDistribution::from_unnormalized(half).unwrap();
+        assert_eq!(uniform.pdf().y_at_x(0.5), Some(1.));
+        let negative = PiecewiseLinearFunction::try_from(vec![(0., -1.), (1., 3.)]).unwrap();
+        assert_eq!(
+            PiecewiseLinearDistribution::from_unnormalized(negative),
+            None
+        );
+        let zero = PiecewiseLinearFunction::constant((0., 1.), 0.).unwrap();
+        assert_eq!(PiecewiseLinearDistribution::from_unnormalized(zero), None);
+        assert_eq!(PiecewiseLinearDistribution::triangular(0., 2., 1.), None);
+        assert_eq!(
+            PiecewiseLinearDistribution::trapezoidal(1., 1., 1., 1.),
+            None
+        );
+    }
+
+    #[test]
+    fn test_cdf_quantile() {
+        let d = PiecewiseLinearDistribution::trapezoidal(0f64, 1., 3., 5.).unwrap();
+        assert_eq!(d.pdf().coordinates.len(), 4);
+        assert_eq!(d.cdf(-1.), 0.);
+        assert_eq!(d.cdf(6.), 1.);
+        assert!((d.cdf(1.) - 1. / 7.).abs() < 1e-12);
+        assert!((d.cdf(3.) - 5. / 7.).abs() < 1e-12);
+        for i in 0..=100 {
+            let p = f64::from(i) / 100.;
+            let x = d.quantile(p).unwrap();
+            assert!((d.cdf(x) - p).abs() < 1e-12, "at {}", p);
+        }
+        assert_eq!(d.quantile(1.5), None);
+
+        // The quantile skips ranges where the density is zero.
+        let gap = PiecewiseLinearDistribution::from_unnormalized(
+            PiecewiseLinearFunction::try_from(vec![
+                (0., 1.),
+                (1., 1.),
+                (1.5, 0.),
+                (2., 0.),
+                (3., 1.),
+            ])
+            .unwrap(),
+        )
+        .unwrap();
+        let p = gap.cdf(1.75);
+        assert_eq!(gap.quantile(p), Some(1.5));
+
+        let left = PiecewiseLinearDistribution::triangular(0., 0., 1.).unwrap();
+        assert_eq!(left.pdf().coordinates.len(), 2);
+        assert_eq!(left.quantile(0.75), Some(0.5));
+    }
+
+    #[test]
+    fn test_moments() {
+        let d = PiecewiseLinearDistribution::triangular(1f64, 2., 6.).unwrap();
+        assert!((d.mean() - 3.).abs() < 1e-12);
+        // The variance of a triangular distribution is (a² + b² + c² - ab - ac - bc) / 18.
+        assert!((d.variance() - (1. + 4. + 36. - 2. - 6. - 12.) / 18.).abs() < 1e-12);
+
+        let irregular = PiecewiseLinearDistribution::from_unnormalized(
+            PiecewiseLinearFunction::try_from(vec![(-2., 0.), (0., 3.), (1., 0.5), (4., 2.)])
+                .unwrap(),
+        )
+        .unwrap();
+        let (mean, variance) = numerical_moments(&irregular);
+        assert!((irregular.mean() - mean).abs() < 1e-6);
+        assert!((irregular.variance() - variance).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_moments_far_from_zero() {
+        let start = 1.7e9f64;
+        let d = PiecewiseLinearDistribution::triangular(start, start + 10., start + 20.).unwrap();
+        assert!((d.mean() - (start + 10.)).abs() < 1e-6);
+        assert!((d.variance() - 300. / 18.).abs() < 1e-6);
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn test_sample() {
+        let d = PiecewiseLinearDistribution::triangular(0., 1., 4.).unwrap();
+        // Evenly spaced values over the whole range of the generator.
+        let mut rng = rand::rngs::mock::StepRng::new(0, u64::MAX / 20_000);
+        let samples: Vec<f64> = (0..20_000).map(|_| d.sample(&mut rng)).collect();
+        assert!(samples.iter().all(|&x| (0. ..=4.).contains(&x)));
+        let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+        assert!((mean - d.mean()).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_cdf_from_samples() {
+        let cdf = PiecewiseLinearCdf::from_samples(&[4f64, 1., 2., 2., 3., 5.]).unwrap();
+        assert_eq!(
+            cdf.function().coordinates,
+            vec![
+                Coord { x: 1., y: 0. },
+                Coord { x: 2., y: 0.3 },
+                Coord { x: 3., y: 0.6 },
+                Coord { x: 4., y: 0.8 },
+                Coord { x: 5., y: 1. },
+            ]
+        );
+        assert_eq!(cdf.cdf(0.), 0.);
+        assert_eq!(cdf.cdf(6.), 1.);
+        assert_eq!(cdf.quantile(0.), Some(1.));
+        assert!((cdf.quantile(0.7).unwrap() - 3.5).abs() < 1e-12);
+        assert_eq!(cdf.quantile(1.), Some(5.));
+        assert_eq!(cdf.quantile(-0.1), None);
+        assert_eq!(PiecewiseLinearCdf::from_samples(&[1., 1.]), None);
+        assert_eq!(PiecewiseLinearCdf::from_samples(&[1., f64::NAN]), None);
+
+        let decreasing =
+            PiecewiseLinearFunction::try_from(vec![(0., 0.), (1., 0.6), (2., 0.4), (3., 1.)])
+                .unwrap();
+        assert_eq!(PiecewiseLinearCdf::new(decreasing), None);
+        let short = PiecewiseLinearFunction::try_from(vec![(0., 0.), (1., 0.9)]).unwrap();
+        assert_eq!(PiecewiseLinearCdf::new(short), None);
+    }
+
+    #[test]
+    fn test_cdf_ks_distance_and_mixture() {
+        let uniform = PiecewiseLinearCdf::from_samples(&[0., 1.]).unwrap();
+        let shifted = PiecewiseLinearCdf::from_samples(&[0.5, 1.5]).unwrap();
+        assert_eq!(uniform.ks_distance(&shifted), 0.5);
+        assert_eq!(shifted.ks_distance(&uniform), 0.5);
+        assert_eq!(uniform.ks_distance(&uniform), 0.);
+
+        let mixture = PiecewiseLinearCdf::mixture(&[(1., &uniform), (3., &shifted)]).unwrap();
+        assert_eq!(mixture.function().domain(), (0., 1.5));
+        for i in -5..=20 {
+            let x = f64::from(i) / 10.;
+            let expected = 0.25 * uniform.cdf(x) + 0.75 * shifted.cdf(x);
+            assert!((mixture.cdf(x) - expected).abs() < 1e-12);
+        }
+        assert_eq!(PiecewiseLinearCdf::<f64>::mixture(&[]), None);
+        assert_eq!(
+            PiecewiseLinearCdf::mixture(&[(-1., &uniform), (2., &shifted)]),
+            None
+        );
+    }
+}
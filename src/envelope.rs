@@ -0,0 +1,288 @@
+// Copyright 2019 Matthieu Felix
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Lower and upper envelopes of sets of segments with arbitrary, possibly overlapping domains.
+
+use std::cmp::Ordering;
+
+use crate::{
+    line_in_domain, line_intersect, y_at_x, Coord, CoordFloat, Line, PiecewiseLinearFunction,
+};
+
+/// Which envelope of a set of segments to compute.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Extremum {
+    /// Keep the smallest value at each point.
+    Lower,
+    /// Keep the greatest value at each point.
+    Upper,
+}
+
+impl Extremum {
+    /// Returns whether `a` is at least as good as `b` for this extremum.
+    pub(crate) fn prefers<T: CoordFloat>(self, a: T, b: T) -> bool {
+        match self {
+            Extremum::Lower => a <= b,
+            Extremum::Upper => a >= b,
+        }
+    }
+
+    /// Returns the order of slopes in the functions for which this extremum is reached at the
+    /// edges of intervals: decreasing slopes (concave functions) for the lower envelope, and
+    /// increasing slopes (convex functions) for the upper one.
+    pub(crate) fn edge_slope_order(self) -> Ordering {
+        match self {
+            Extremum::Lower => Ordering::Greater,
+            Extremum::Upper => Ordering::Less,
+        }
+    }
+}
+
+/// The envelope of a set of finite segments.
+///
+/// The envelope is stored as a list of segments sorted by `x`, whose domains do not overlap (but
+/// may touch). It may have holes until enough segments have been inserted.
+#[derive(Clone, Debug)]
+pub(crate) struct Envelope<T: CoordFloat> {
+    extremum: Extremum,
+    segments: Vec<Line<T>>,
+}
+
+impl<T: CoordFloat> Envelope<T> {
+    pub(crate) fn new(extremum: Extremum) -> Self {
+        Envelope {
+            extremum,
+            segments: Vec::new(),
+        }
+    }
+
+    /// Inserts all segments of a function into the envelope.
+    pub(crate) fn insert_function(&mut self, f: &PiecewiseLinearFunction<T>) {
+        for segment in f.segments_iter() {
+            self.insert(segment);
+        }
+    }
+
+    /// Inserts a segment into the envelope.
+    ///
+    /// The complexity of this method is linear in the number of segments of the envelope.
+    pub(crate) fn insert(&mut self, line: Line<T>) {
+        if line.start.x >= line.end.x {
+            return;
+        }
+        let mut new_segments = Vec::with_capacity(self.segments.len() + 3);
+        // Start of the part of `line` which hasn't been emitted or compared yet.
+        let mut cursor = line.start.x;
+
+        for &existing in &self.segments {
+            if existing.end.x <= line.start.x || existing.start.x >= line.end.x {
+                if existing.start.x >= line.end.x && cursor < line.end.x {
+                    new_segments.extend(line_in_domain(&line, (cursor, line.end.x)));
+                    cursor = line.end.x;
+                }
+                new_segments.push(existing);
+                continue;
+            }
+
+            if existing.start.x > cursor {
+                new_segments.extend(line_in_domain(&line, (cursor, existing.start.x)));
+            }
+            if existing.start.x < line.start.x {
+                new_segments.extend(line_in_domain(&existing, (existing.start.x, line.start.x)));
+            }
+
+            let overlap = (
+                existing.start.x.max(line.start.x),
+                existing.end.x.min(line.end.x),
+            );
+            self.push_best(&mut new_segments, &existing, &line, overlap);
+
+            if existing.end.x > line.end.x {
+                new_segments.extend(line_in_domain(&existing, (line.end.x, existing.end.x)));
+            }
+            cursor = cursor.max(overlap.1);
+        }
+        if cursor < line.end.x {
+            new_segments.extend(line_in_domain(&line, (cursor, line.end.x)));
+        }
+        self.segments = new_segments;
+    }
+
+    /// Pushes the best of two segments over the domain `overlap`, splitting it if they cross.
+    fn push_best(
+        &self,
+        new_segments: &mut Vec<Line<T>>,
+        a: &Line<T>,
+        b: &Line<T>,
+        overlap: (T, T),
+    ) {
+        let (a, b) = match (line_in_domain(a, overlap), line_in_domain(b, overlap)) {
+            (Some(a), Some(b)) => (a, b),
+            _ => return,
+        };
+        let a_first = self.extremum.prefers(a.start.y, b.start.y);
+        let a_last = self.extremum.prefers(a.end.y, b.end.y);
+        let b_first = self.extremum.prefers(b.start.y, a.start.y);
+        let b_last = self.extremum.prefers(b.end.y, a.end.y);
+        // Segments which meet at one end are not crossing: keep the best one at the other end.
+        if a_first && a_last {
+            new_segments.push(a);
+            return;
+        } else if b_first && b_last {
+            new_segments.push(b);
+            return;
+        }
+        let (x, _) = line_intersect(&a, &b);
+        if x > overlap.0 && x < overlap.1 {
+            let (first, second) = if a_first { (a, b) } else { (b, a) };
+            // Both halves share the crossing so that rounding cannot open a gap between them.
+            let crossing = Coord {
+                x,
+                y: y_at_x(&first, x),
+            };
+            new_segments.push(Line::new(first.start, crossing));
+            new_segments.push(Line::new(crossing, second.end));
+        } else {
+            // The crossing is lost to rounding; keep the segment that wins over most of the domain,
+            // which is the one that wins by the largest margin at either end.
+            let a_wins = if (a.start.y - b.start.y).abs() >= (a.end.y - b.end.y).abs() {
+                a_first
+            } else {
+                a_last
+            };
+            new_segments.push(if a_wins { a } else { b });
+        }
+    }
+
+    /// Converts the envelope to a function.
+    ///
+    /// Returns `None` if the envelope is empty, has holes, or jumps between two segments, up to
+    /// rounding errors.
+    pub(crate) fn into_function(self) -> Option<PiecewiseLinearFunction<T>> {
+        let tolerance = T::epsilon() * T::from(16).unwrap();
+        let mut coordinates = Vec::with_capacity(self.segments.len() + 1);
+        coordinates.push(self.segments.first()?.start);
+        let mut previous = None;
+        for segment in self.segments {
+            // This unwrap is guaranteed to succeed as a coordinate was pushed above.
+            let end = *coordinates.last().unwrap();
+            // Segments are truncated by interpolation, so the rounding error grows with the values
+            // at their ends and with their slopes.
+            let scale = previous
+                .iter()
+                .chain(std::iter::once(&segment))
+                .flat_map(|l: &Line<T>| {
+                    let slope = (l.end.y - l.start.y) / (l.end.x - l.start.x);
+                    [l.start.y, l.end.y, slope * end.x]
+                })
+                .fold(T::one(), |scale, y| scale.max(y.abs()));
+            if segment.start.x != end.x || (segment.start.y - end.y).abs() > tolerance * scale {
+                return None;
+            }
+            previous = Some(segment);
+            coordinates.push(segment.end);
+        }
+        PiecewiseLinearFunction::new(remove_collinear_points(coordinates))
+    }
+}
+
+/// Removes points which lie on the segment joining their neighbours, up to rounding errors.
+pub(crate) fn remove_collinear_points<T: CoordFloat>(coordinates: Vec<Coord<T>>) -> Vec<Coord<T>> {
+    let tolerance = T::epsilon() * T::from(16).unwrap();
+    let mut result: Vec<Coord<T>> = Vec::with_capacity(coordinates.len());
+    for (i, &point) in coordinates.iter().enumerate() {
+        if result.is_empty() || i + 1 == coordinates.len() {
+            result.push(point);
+            continue;
+        }
+        let previous = *result.last().unwrap();
+        let next = coordinates[i + 1];
+        if !(previous.x.is_finite() && next.x.is_finite()) {
+            result.push(point);
+            continue;
+        }
+        let expected = y_at_x(&Line::new(previous, next), point.x);
+        let scale = previous.y.abs().max(next.y.abs()).max(point.y.abs());
+        if (expected - point.y).abs() > tolerance * scale {
+            result.push(point);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use super::*;
+
+    #[test]
+    fn test_envelope() {
+        let segments = vec![
+            Line::new((0., 0.), (2., 2.)),
+            Line::new((1., 2.), (3., 0.)),
+            Line::new((0., 3.), (3., 3.)),
+        ];
+        let mut lower = Envelope::new(Extremum::Lower);
+        let mut upper = Envelope::new(Extremum::Upper);
+        for &segment in &segments {
+            lower.insert(segment);
+            upper.insert(segment);
+        }
+        assert_eq!(
+            lower.into_function().unwrap(),
+            PiecewiseLinearFunction::try_from(vec![(0., 0.), (1.5, 1.5), (3., 0.)]).unwrap()
+        );
+        assert_eq!(
+            upper.into_function().unwrap(),
+            PiecewiseLinearFunction::constant((0., 3.), 3.).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_envelope_touching_segments() {
+        // Segments which meet at one end, inserted in both orders.
+        let (a, b) = (
+            Line::new((4., 6.5), (5., 7.5)),
+            Line::new((4., 7.), (5., 7.5)),
+        );
+        for &(first, second) in &[(a, b), (b, a)] {
+            let mut lower = Envelope::new(Extremum::Lower);
+            let mut upper = Envelope::new(Extremum::Upper);
+            for &segment in &[first, second] {
+                lower.insert(segment);
+                upper.insert(segment);
+            }
+            assert_eq!(lower.segments, vec![a]);
+            assert_eq!(upper.segments, vec![b]);
+        }
+    }
+
+    #[test]
+    fn test_envelope_with_hole() {
+        let mut envelope = Envelope::new(Extremum::Lower);
+        envelope.insert(Line::new((0., 0.), (2., 2.)));
+        envelope.insert(Line::new((2.5, 1.), (3., 1.)));
+        assert!(envelope.clone().into_function().is_none());
+        envelope.insert(Line::new((1.5, 1.5), (2.5, 1.)));
+        let mut jump = envelope.clone();
+        jump.insert(Line::new((3., 5.), (4., 5.)));
+        assert!(jump.into_function().is_none());
+        assert_eq!(
+            envelope.into_function().unwrap(),
+            PiecewiseLinearFunction::try_from(vec![(0., 0.), (1.5, 1.5), (2.5, 1.), (3., 1.)])
+                .unwrap()
+        );
+    }
+}
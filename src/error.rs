@@ -0,0 +1,83 @@
+// Copyright 2019 Matthieu Felix
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Error type for operations that can fail in more than one way.
+
+use std::fmt;
+
+/// An error returned by operations on piecewise linear functions.
+///
+/// Operations which can only fail in one obvious way, such as a domain mismatch, return an
+/// `Option` instead.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    /// The operation requires a convex function.
+    NotConvex,
+    /// The function has, or would have, fewer than two points, i.e. its domain is a singleton.
+    TooFewPoints,
+    /// The _x_ values of the points of the function are not strictly increasing.
+    UnsortedPoints,
+    /// The function has no point with a finite _x_ value.
+    NoFinitePoint,
+    /// The result is infinite everywhere.
+    Unbounded,
+    /// The operation requires a function with a bounded domain.
+    UnboundedDomain,
+    /// No solution satisfies the constraints of the problem.
+    Infeasible,
+    /// The functions do not have the same domain.
+    DomainMismatch,
+    /// Input could not be parsed. Lines are numbered from 1.
+    Parse { line: usize, message: String },
+    /// Reading or writing failed.
+    Io(String),
+    /// Binary input could not be decoded.
+    Decode(String),
+    /// The points are duplicated, or do not span a two-dimensional domain.
+    Degenerate,
+    /// A variable index is out of range.
+    UnknownVariable(usize),
+    /// A name is already used in the model.
+    DuplicateName(String),
+}
+
+impl From<::std::io::Error> for Error {
+    fn from(error: ::std::io::Error) -> Self {
+        Error::Io(error.to_string())
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NotConvex => write!(f, "the function is not convex"),
+            Error::TooFewPoints => write!(f, "the function has fewer than two points"),
+            Error::UnsortedPoints => write!(f, "the x values of the points are not increasing"),
+            Error::NoFinitePoint => write!(f, "the function has no point with a finite x value"),
+            Error::Unbounded => write!(f, "the result is infinite everywhere"),
+            Error::UnboundedDomain => write!(f, "the domain of the function is not bounded"),
+            Error::Infeasible => write!(f, "the problem has no solution"),
+            Error::DomainMismatch => write!(f, "the functions do not have the same domain"),
+            Error::Parse { line, message } => write!(f, "line {}: {}", line, message),
+            Error::Io(message) => write!(f, "I/O error: {}", message),
+            Error::Decode(message) => write!(f, "invalid binary input: {}", message),
+            Error::Degenerate => write!(f, "the points do not span a two-dimensional domain"),
+            Error::UnknownVariable(index) => write!(f, "no variable has index {}", index),
+            Error::DuplicateName(name) => write!(f, "the name {} is already used", name),
+        }
+    }
+}
+
+impl ::std::error::Error for Error {}
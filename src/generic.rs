@@ -0,0 +1,359 @@
+// Copyright 2019 Matthieu Felix
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Piecewise linear functions with different types for the x and y axes.
+
+use std::convert::TryFrom;
+use std::ops::{Add, Sub};
+use std::time::Duration;
+
+use crate::{Coord, CoordFloat, Error, PiecewiseLinearFunction};
+
+/// Types which can be used for the x axis of a `Plf`.
+///
+/// They only need to be ordered, and to give the distance between two values as a float.
+pub trait Abscissa: Copy + PartialOrd {
+    /// Returns `self - origin` as a float.
+    fn delta(self, origin: Self) -> f64;
+
+    /// Returns whether `self` is finite. Only floats can be infinite.
+    fn is_finite(self) -> bool {
+        true
+    }
+}
+
+/// Types which can be used for the y axis of a `Plf`, that is, elements of a vector space over
+/// the floats.
+///
+/// This is implemented for `f32` and `f64`. Arrays do not implement `Add`, so functions with
+/// vector values are represented by `PiecewiseLinearVectorFunction` instead.
+pub trait Ordinate: Copy + Add<Output = Self> + Sub<Output = Self> {
+    /// Multiplies `self` by a scalar.
+    fn scale(self, factor: f64) -> Self;
+}
+
+macro_rules! impl_integer_abscissa {
+    ($($t:ty),*) => {
+        $(impl Abscissa for $t {
+            fn delta(self, origin: Self) -> f64 {
+                // Computed on 128 bits so that the difference cannot overflow.
+                (i128::from(self) - i128::from(origin)) as f64
+            }
+        })*
+    };
+}
+
+impl_integer_abscissa!(i8, i16, i32, i64, u8, u16, u32, u64);
+
+impl Abscissa for f32 {
+    fn delta(self, origin: Self) -> f64 {
+        f64::from(self) - f64::from(origin)
+    }
+
+    fn is_finite(self) -> bool {
+        f32::is_finite(self)
+    }
+}
+
+impl Abscissa for f64 {
+    fn delta(self, origin: Self) -> f64 {
+        self - origin
+    }
+
+    fn is_finite(self) -> bool {
+        f64::is_finite(self)
+    }
+}
+
+impl Abscissa for Duration {
+    /// Returns the difference in seconds.
+    fn delta(self, origin: Self) -> f64 {
+        if self >= origin {
+            (self - origin).as_secs_f64()
+        } else {
+            -(origin - self).as_secs_f64()
+        }
+    }
+}
+
+impl Ordinate for f32 {
+    fn scale(self, factor: f64) -> Self {
+        (f64::from(self) * factor) as f32
+    }
+}
+
+impl Ordinate for f64 {
+    fn scale(self, factor: f64) -> Self {
+        self * factor
+    }
+}
+
+/// A continuous piecewise linear function, whose x values are of type `X` and y values of type
+/// `Y`.
+///
+/// Unlike `PiecewiseLinearFunction`, which stores both values in a `geo::Coord`, x values do not
+/// need to be floats: they can be integer timestamps or `Duration`s, for instance. Only the
+/// distances between x values are converted to floats to interpolate between points, so values
+/// at the points themselves are exact. Distances between integers are exact as long as they are
+/// below 2^53.
+///
+/// The points are sorted by strictly increasing x value, and there are at least two of them.
+/// Infinite domains are not supported.
+///
+/// ## Example
+///
+/// ```
+/// use piecewise_linear::Plf;
+/// // Nanosecond timestamps.
+/// let f = Plf::new(vec![(1_600_000_000_000_000_000i64, 1.), (1_600_000_000_000_000_010, 2.)])
+///     .unwrap();
+/// assert_eq!(f.y_at(1_600_000_000_000_000_005), Some(1.5));
+/// ```
+#[derive(PartialEq, Clone, Debug)]
+pub struct Plf<X, Y> {
+    points: Vec<(X, Y)>,
+}
+
+impl<X: Abscissa, Y: Ordinate> Plf<X, Y> {
+    /// Creates a new function from its points.
+    ///
+    /// Returns `Error::TooFewPoints` if there are fewer than two points, `Error::UnsortedPoints`
+    /// if their x values are not strictly increasing, and `Error::UnboundedDomain` if any of them
+    /// is infinite.
+    pub fn new(points: Vec<(X, Y)>) -> Result<Self, Error> {
+        if points.len() < 2 {
+            Err(Error::TooFewPoints)
+        } else if !points.windows(2).all(|w| w[0].0 < w[1].0) {
+            Err(Error::UnsortedPoints)
+        } else if !points.iter().all(|p| p.0.is_finite()) {
+            Err(Error::UnboundedDomain)
+        } else {
+            Ok(Plf { points })
+        }
+    }
+
+    /// Returns the points of the function.
+    pub fn points(&self) -> &[(X, Y)] {
+        &self.points
+    }
+
+    /// Returns the domain of the function, represented as its min and max.
+    pub fn domain(&self) -> (X, X) {
+        // This unwrap is guaranteed to succeed as there are at least two points.
+        (self.points[0].0, self.points.last().unwrap().0)
+    }
+
+    /// Computes the value f(x).
+    ///
+    /// Returns `None` if `x` is outside the domain of f. At the points of the function, the
+    /// returned value is exactly the stored one.
+    pub fn y_at(&self, x: X) -> Option<Y> {
+        // Index of the first point whose x value is at least `x`.
+        let idx = self.points.partition_point(|p| p.0 < x);
+        match self.points.get(idx) {
+            Some(&(px, py)) if px == x => Some(py),
+            Some(&point) if idx > 0 => Some(interpolate(self.points[idx - 1], point, x)),
+            _ => None,
+        }
+    }
+
+    /// Sums this function with another one.
+    ///
+    /// Both functions must have the same domain; returns `None` otherwise.
+    pub fn add(&self, other: &Plf<X, Y>) -> Option<Plf<X, Y>> {
+        if self.domain() != other.domain() {
+            return None;
+        }
+        let (a, b) = (&self.points, &other.points);
+        let mut points = Vec::with_capacity(a.len() + b.len());
+        let (mut i, mut j) = (0, 0);
+        while i < a.len() && j < b.len() {
+            let (x, y) = if a[i].0 == b[j].0 {
+                i += 1;
+                j += 1;
+                (a[i - 1].0, a[i - 1].1 + b[j - 1].1)
+            } else if a[i].0 < b[j].0 {
+                i += 1;
+                // j > 0 here, as both functions start at the same x.
+                (
+                    a[i - 1].0,
+                    a[i - 1].1 + interpolate(b[j - 1], b[j], a[i - 1].0),
+                )
+            } else {
+                j += 1;
+                (
+                    b[j - 1].0,
+                    interpolate(a[i - 1], a[i], b[j - 1].0) + b[j - 1].1,
+                )
+            };
+            points.push((x, y));
+        }
+        Some(Plf { points })
+    }
+
+    /// Returns the function multiplied by `factor`.
+    pub fn scale(&self, factor: f64) -> Plf<X, Y> {
+        Plf {
+            points: self
+                .points
+                .iter()
+                .map(|&(x, y)| (x, y.scale(factor)))
+                .collect(),
+        }
+    }
+
+    /// Returns the integral of the function over its domain, with the distances between x values
+    /// given by `Abscissa::delta()`.
+    pub fn integrate(&self) -> Y {
+        let mut pieces = self
+            .points
+            .windows(2)
+            .map(|w| (w[0].1 + w[1].1).scale(w[1].0.delta(w[0].0) / 2.));
+        // This unwrap is guaranteed to succeed as there are at least two points.
+        let first = pieces.next().unwrap();
+        pieces.fold(first, |total, piece| total + piece)
+    }
+}
+
+impl<X: Abscissa> Plf<X, f64> {
+    /// Converts this function to a `PiecewiseLinearFunction`, with each x value replaced by its
+    /// distance to `origin`.
+    ///
+    /// This makes all the operations of `PiecewiseLinearFunction` available, at the cost of
+    /// representing x values as floats.
+    pub fn to_function(&self, origin: X) -> Result<PiecewiseLinearFunction<f64>, Error> {
+        PiecewiseLinearFunction::try_new(
+            self.points
+                .iter()
+                .map(|&(x, y)| Coord {
+                    x: x.delta(origin),
+                    y,
+                })
+                .collect(),
+        )
+    }
+}
+
+/// Interpolates linearly between points `start` and `end` at `x`.
+fn interpolate<X: Abscissa, Y: Ordinate>(start: (X, Y), end: (X, Y), x: X) -> Y {
+    let t = x.delta(start.0) / end.0.delta(start.0);
+    start.1 + (end.1 - start.1).scale(t)
+}
+
+impl<T: CoordFloat + Abscissa + Ordinate> TryFrom<PiecewiseLinearFunction<T>> for Plf<T, T> {
+    type Error = Error;
+
+    /// Returns `Error::UnboundedDomain` if the function has an infinite domain.
+    fn try_from(value: PiecewiseLinearFunction<T>) -> Result<Self, Self::Error> {
+        if !value.is_bounded() {
+            return Err(Error::UnboundedDomain);
+        }
+        Ok(Plf {
+            points: value.coordinates.iter().map(|c| c.x_y()).collect(),
+        })
+    }
+}
+
+impl<T: CoordFloat + Abscissa + Ordinate> TryFrom<Plf<T, T>> for PiecewiseLinearFunction<T> {
+    type Error = Error;
+
+    fn try_from(value: Plf<T, T>) -> Result<Self, Self::Error> {
+        PiecewiseLinearFunction::try_new(value.points.into_iter().map(Coord::from).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const T0: i64 = 1_600_000_000_000_000_000;
+
+    #[test]
+    fn test_new() {
+        assert_eq!(Plf::new(vec![(0i64, 1.)]), Err(Error::TooFewPoints));
+        assert_eq!(
+            Plf::new(vec![(0i64, 1.), (0, 2.)]),
+            Err(Error::UnsortedPoints)
+        );
+        assert_eq!(
+            Plf::new(vec![(0f64, 1f64), (f64::INFINITY, 2.)]),
+            Err(Error::UnboundedDomain)
+        );
+        assert_eq!(
+            Plf::new(vec![(f32::NEG_INFINITY, 1f32), (0., 2.)]),
+            Err(Error::UnboundedDomain)
+        );
+        let f = Plf::new(vec![(0i64, 1.), (10, 2.)]).unwrap();
+        assert_eq!(f.domain(), (0, 10));
+        assert_eq!(f.points(), &[(0, 1.), (10, 2.)]);
+    }
+
+    #[test]
+    fn test_integer_timestamps() {
+        // These timestamps are not exactly representable as f64.
+        let f = Plf::new(vec![(T0 + 1, 0.1), (T0 + 3, 0.7), (T0 + 1003, -0.3)]).unwrap();
+        assert_eq!(f.y_at(T0 + 1), Some(0.1));
+        assert_eq!(f.y_at(T0 + 3), Some(0.7));
+        assert_eq!(f.y_at(T0 + 1003), Some(-0.3));
+        assert!((f.y_at(T0 + 503).unwrap() - 0.2f64).abs() < 1e-12);
+        assert_eq!(f.y_at(T0), None);
+        assert_eq!(f.y_at(T0 + 1004), None);
+        assert_eq!(f.y_at(i64::MAX), None);
+        assert_eq!(f.y_at(i64::MIN), None);
+    }
+
+    #[test]
+    fn test_durations() {
+        let f = Plf::new(vec![
+            (Duration::from_millis(0), 0f64),
+            (Duration::from_secs(2), 4.),
+        ])
+        .unwrap();
+        assert_eq!(f.y_at(Duration::from_millis(500)), Some(1.));
+        assert_eq!(f.integrate(), 4.);
+        assert_eq!(
+            f.to_function(Duration::from_secs(1)).unwrap(),
+            PiecewiseLinearFunction::try_from(vec![(-1., 0.), (1., 4.)]).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_operations() {
+        let f = Plf::new(vec![(0u32, 0f32), (4, 4.), (8, 0.)]).unwrap();
+        let g = Plf::new(vec![(0u32, 1f32), (2, 1.), (6, 3.), (8, 3.)]).unwrap();
+        assert_eq!(
+            f.add(&g).unwrap(),
+            Plf::new(vec![(0, 1.), (2, 3.), (4, 6.), (6, 5.), (8, 3.)]).unwrap()
+        );
+        assert_eq!(f.add(&f.scale(2.)).unwrap(), f.scale(3.));
+        assert_eq!(f.integrate(), 16.);
+        let short = Plf::new(vec![(0u32, 0f32), (4, 4.)]).unwrap();
+        assert_eq!(f.add(&short), None);
+    }
+
+    #[test]
+    fn test_conversions() {
+        let f = PiecewiseLinearFunction::try_from(vec![(0., 1.), (1., 3.), (2., 0.)]).unwrap();
+        let g: Plf<f64, f64> = Plf::try_from(f.clone()).unwrap();
+        assert_eq!(g.y_at(0.5), f.y_at_x(0.5));
+        assert_eq!(PiecewiseLinearFunction::try_from(g), Ok(f));
+        let unbounded =
+            PiecewiseLinearFunction::with_edge_slopes(vec![(0., 1.).into()], None, Some(1.))
+                .unwrap();
+        assert_eq!(
+            Plf::<f64, f64>::try_from(unbounded),
+            Err(Error::UnboundedDomain)
+        );
+    }
+}
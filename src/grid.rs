@@ -0,0 +1,257 @@
+// Copyright 2019 Matthieu Felix
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Bilinear lookup tables on rectilinear grids.
+
+use crate::triangulated::{check_grid, grid_cell};
+use crate::{y_at_x, Coord, CoordFloat, Error, Line, PiecewiseLinearFunction};
+
+/// A continuous function of two variables `x1` and `x2`, interpolated bilinearly on the cells of a
+/// rectilinear grid.
+///
+/// Along lines of constant `x1` or `x2`, the function is piecewise linear, with points of
+/// inflection on the grid lines. Slices and partial integrals are therefore returned exactly as
+/// `PiecewiseLinearFunction`s.
+///
+/// ## Example
+///
+/// ```
+/// use piecewise_linear::GridFunction2D;
+/// // f(x1, x2) = x1 * x2 on [0, 2] x [0, 1].
+/// let f = GridFunction2D::new(vec![0., 2.], vec![0., 1.], vec![0., 0., 0., 2.]).unwrap();
+/// assert_eq!(f.y_at(1., 0.5), Some(0.5));
+/// assert_eq!(f.slice_at_x1(1.).unwrap().y_at_x(1.), Some(1.));
+/// assert_eq!(f.integrate(), 1.);
+/// ```
+#[derive(PartialEq, Clone, Debug)]
+pub struct GridFunction2D<T: CoordFloat> {
+    x1: Vec<T>,
+    x2: Vec<T>,
+    /// The value at `(x1[i], x2[j])` is `values[i * x2.len() + j]`.
+    values: Vec<T>,
+}
+
+impl<T: CoordFloat> GridFunction2D<T> {
+    /// Creates a function on the grid with lines `x1` and `x2`, where the value at
+    /// `(x1[i], x2[j])` is `values[i * x2.len() + j]`.
+    ///
+    /// Returns `Error::TooFewPoints` if there are fewer than two lines in either direction,
+    /// `Error::UnsortedPoints` if they are not strictly increasing, `Error::UnboundedDomain` if
+    /// any of them is infinite, and `Error::DomainMismatch` if there is not one value per node.
+    pub fn new(x1: Vec<T>, x2: Vec<T>, values: Vec<T>) -> Result<Self, Error> {
+        check_grid(&x1, &x2, values.len())?;
+        Ok(GridFunction2D { x1, x2, values })
+    }
+
+    /// Returns the grid lines along `x1`.
+    pub fn x1(&self) -> &[T] {
+        &self.x1
+    }
+
+    /// Returns the grid lines along `x2`.
+    pub fn x2(&self) -> &[T] {
+        &self.x2
+    }
+
+    /// Returns the value at the node `(x1[i], x2[j])`.
+    ///
+    /// Panics if `i` or `j` is out of bounds.
+    pub fn value(&self, i: usize, j: usize) -> T {
+        assert!(j < self.x2.len());
+        self.values[i * self.x2.len() + j]
+    }
+
+    /// Computes the value f(x1, x2).
+    ///
+    /// The function is interpolated along `x1` first, so the result is the same as `y_at_x(x2)`
+    /// on `slice_at_x1(x1)`. Returns `None` if the point is outside the domain of f.
+    pub fn y_at(&self, x1: T, x2: T) -> Option<T> {
+        let i = grid_cell(&self.x1, x1)?;
+        let j = grid_cell(&self.x2, x2)?;
+        let low = self.interpolate_x1(i, j, x1);
+        let high = self.interpolate_x1(i, j + 1, x1);
+        Some(y_at_x(
+            &Line::new((self.x2[j], low), (self.x2[j + 1], high)),
+            x2,
+        ))
+    }
+
+    /// Returns the function x2 ↦ f(x1, x2) at the given `x1`.
+    ///
+    /// Returns `None` if `x1` is outside the domain of f.
+    pub fn slice_at_x1(&self, x1: T) -> Option<PiecewiseLinearFunction<T>> {
+        let i = grid_cell(&self.x1, x1)?;
+        Some(PiecewiseLinearFunction {
+            coordinates: (0..self.x2.len())
+                .map(|j| Coord {
+                    x: self.x2[j],
+                    y: self.interpolate_x1(i, j, x1),
+                })
+                .collect(),
+        })
+    }
+
+    /// Returns the function x1 ↦ f(x1, x2) at the given `x2`.
+    ///
+    /// Returns `None` if `x2` is outside the domain of f.
+    pub fn slice_at_x2(&self, x2: T) -> Option<PiecewiseLinearFunction<T>> {
+        let j = grid_cell(&self.x2, x2)?;
+        Some(PiecewiseLinearFunction {
+            coordinates: (0..self.x1.len())
+                .map(|i| {
+                    let line = Line::new(
+                        (self.x2[j], self.value(i, j)),
+                        (self.x2[j + 1], self.value(i, j + 1)),
+                    );
+                    Coord {
+                        x: self.x1[i],
+                        y: y_at_x(&line, x2),
+                    }
+                })
+                .collect(),
+        })
+    }
+
+    /// Returns the function x2 ↦ ∫ f(x1, x2) dx1, integrated over the whole range of `x1`.
+    pub fn integrate_along_x1(&self) -> PiecewiseLinearFunction<T> {
+        PiecewiseLinearFunction {
+            coordinates: (0..self.x2.len())
+                .map(|j| Coord {
+                    x: self.x2[j],
+                    y: trapezoids(&self.x1, |i| self.value(i, j)),
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns the function x1 ↦ ∫ f(x1, x2) dx2, integrated over the whole range of `x2`.
+    pub fn integrate_along_x2(&self) -> PiecewiseLinearFunction<T> {
+        PiecewiseLinearFunction {
+            coordinates: (0..self.x1.len())
+                .map(|i| Coord {
+                    x: self.x1[i],
+                    y: trapezoids(&self.x2, |j| self.value(i, j)),
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns the integral of the function over its domain.
+    pub fn integrate(&self) -> T {
+        let along_x2 = self.integrate_along_x2();
+        trapezoids(&self.x1, |i| along_x2.coordinates[i].y)
+    }
+
+    /// Interpolates along `x1` in cell `i`, on the grid line `x2[j]`.
+    fn interpolate_x1(&self, i: usize, j: usize, x1: T) -> T {
+        let line = Line::new(
+            (self.x1[i], self.value(i, j)),
+            (self.x1[i + 1], self.value(i + 1, j)),
+        );
+        y_at_x(&line, x1)
+    }
+}
+
+/// Integrates exactly the piecewise linear function with value `value(k)` at `lines[k]`.
+fn trapezoids<T: CoordFloat>(lines: &[T], value: impl Fn(usize) -> T) -> T {
+    let two = T::one() + T::one();
+    (1..lines.len())
+        .map(|k| (lines[k] - lines[k - 1]) * (value(k - 1) + value(k)) / two)
+        .fold(T::zero(), |total, part| total + part)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use super::*;
+
+    /// f(x1, x2) = x1 * x2 + x1 + 1 on a grid over [0, 3] x [0, 2], which it interpolates exactly.
+    fn get_test_function() -> GridFunction2D<f64> {
+        let (x1, x2) = (vec![0., 1., 3.], vec![0., 0.5, 2.]);
+        let values = x1
+            .iter()
+            .flat_map(|a| x2.iter().map(move |b| a * b + a + 1.))
+            .collect();
+        GridFunction2D::new(x1, x2, values).unwrap()
+    }
+
+    #[test]
+    fn test_new() {
+        assert_eq!(
+            GridFunction2D::new(vec![0.], vec![0., 1.], vec![0., 0.]),
+            Err(Error::TooFewPoints)
+        );
+        assert_eq!(
+            GridFunction2D::new(vec![0., 1.], vec![0., 0.], vec![0.; 4]),
+            Err(Error::UnsortedPoints)
+        );
+        assert_eq!(
+            GridFunction2D::new(vec![0., f64::INFINITY], vec![0., 1.], vec![0.; 4]),
+            Err(Error::UnboundedDomain)
+        );
+        assert_eq!(
+            GridFunction2D::new(vec![0., 1.], vec![0., 1.], vec![0.; 5]),
+            Err(Error::DomainMismatch)
+        );
+    }
+
+    #[test]
+    fn test_y_at() {
+        let f = get_test_function();
+        assert_eq!(f.value(2, 1), 5.5);
+        assert_eq!(f.y_at(3., 0.5), Some(5.5));
+        assert_eq!(f.y_at(2., 1.), Some(5.));
+        assert_eq!(f.y_at(0.5, 1.5), Some(2.25));
+        assert_eq!(f.y_at(3., 2.), Some(10.));
+        assert_eq!(f.y_at(-0.1, 1.), None);
+        assert_eq!(f.y_at(1., 2.1), None);
+    }
+
+    #[test]
+    fn test_slices() {
+        let f = get_test_function();
+        let at_x1 = f.slice_at_x1(2.).unwrap();
+        assert_eq!(
+            at_x1,
+            PiecewiseLinearFunction::try_from(vec![(0., 3.), (0.5, 4.), (2., 7.)]).unwrap()
+        );
+        for &x2 in &[0., 0.3, 0.5, 1.7, 2.] {
+            assert_eq!(at_x1.y_at_x(x2), f.y_at(2., x2));
+        }
+        assert_eq!(
+            f.slice_at_x2(1.).unwrap(),
+            PiecewiseLinearFunction::try_from(vec![(0., 1.), (1., 3.), (3., 7.)]).unwrap()
+        );
+        assert_eq!(f.slice_at_x1(4.), None);
+        assert_eq!(f.slice_at_x2(-1.), None);
+    }
+
+    #[test]
+    fn test_integration() {
+        let f = get_test_function();
+        // ∫ (x1 x2 + x1 + 1) dx2 over [0, 2] = 2 x1 + 2 x1 + 2.
+        assert_eq!(
+            f.integrate_along_x2(),
+            PiecewiseLinearFunction::try_from(vec![(0., 2.), (1., 6.), (3., 14.)]).unwrap()
+        );
+        // ∫ (x1 x2 + x1 + 1) dx1 over [0, 3] = 4.5 x2 + 4.5 + 3.
+        assert_eq!(
+            f.integrate_along_x1(),
+            PiecewiseLinearFunction::try_from(vec![(0., 7.5), (0.5, 9.75), (2., 16.5)]).unwrap()
+        );
+        assert_eq!(f.integrate(), f.integrate_along_x1().integrate());
+        assert_eq!(f.integrate(), 24.);
+    }
+}
@@ -27,19 +27,36 @@
 //! Most methods will refuse to operate on two (or more) functions that do not have the same
 //! domain. You can use `expand_domain()` and `shrink_domain()` to adapt domains.
 //!
-//! Domains over all real numbers should be possible by using ±inf _x_ values, but this has not
-//! been extensively tested.
+//! ### Infinite domains
+//!
+//! Functions can be defined over half-infinite or infinite domains. Since the value of a function
+//! at ±inf carries no information, the first or last coordinate of such a function stores the
+//! _slope_ of the edge piece in its _y_ value instead. For instance, the coordinates
+//! `[(-inf, 0.), (0., 1.), (inf, 2.)]` represent the function that is constant and equal to 1 on
+//! ]-inf, 0], then grows with a slope of 2 on [0, inf[. A function must have at least one point
+//! with a finite _x_ value, so that its edge pieces are anchored somewhere.
+//!
+//! `with_edge_slopes()` is a convenient way to build such functions. Evaluating a function at ±inf
+//! returns the limit of the function at that point, and `integrate()` returns ±inf (or NaN) when
+//! the integral diverges.
 //!
 //! ## Numeric types
 //!
 //! This crate should support functions using any `CoordFloat` (more or less a rust-num `Num`),
 //! however it has not been tested with types other than `f32` and `f64`.
+//!
+//! ## Optional features
+//!
+//! - `serde`: implements `Serialize` and `Deserialize` for `PiecewiseLinearFunction`, as an array
+//!   of `[x, y]` pairs.
+//! - `rand`: enables sampling from distributions in the `distribution` module.
 
 extern crate geo;
 extern crate num_traits;
 
+#[cfg(feature = "rand")]
+extern crate rand;
 #[cfg(feature = "serde")]
-#[macro_use]
 extern crate serde;
 
 use std::cmp::Ordering;
@@ -49,6 +66,44 @@ use std::convert::{TryFrom, TryInto};
 pub use geo::{Coord, CoordFloat, Line, LineString, Point};
 use num_traits::Signed;
 
+mod allocation;
+mod borrowed;
+mod conjugate;
+mod convexity;
+mod convolution;
+mod csv;
+mod envelope;
+mod error;
+mod generic;
+mod grid;
+mod lut;
+mod moments;
+mod norms;
+mod periodic;
+#[cfg(feature = "serde")]
+mod serialization;
+mod triangulated;
+mod vector;
+
+pub mod binary;
+pub mod distribution;
+pub mod lp;
+pub mod network_calculus;
+pub mod ttf;
+
+pub use allocation::{allocate, Allocation};
+pub use borrowed::PiecewiseLinearFunctionRef;
+pub use conjugate::ConjugateStrategy;
+pub use convolution::infimal_convolution;
+pub use csv::{read_csv_functions, write_csv_functions, CsvOptions};
+pub use error::Error;
+pub use generic::{Abscissa, Ordinate, Plf};
+pub use grid::GridFunction2D;
+pub use lut::UniformLut;
+pub use periodic::PeriodicPiecewiseLinearFunction;
+pub use triangulated::TriangulatedFunction;
+pub use vector::PiecewiseLinearVectorFunction;
+
 /// A continuous piecewise linear function.
 ///
 /// The function is represented as a list of `(x, y)` pairs, each representing a point of
@@ -61,6 +116,10 @@ use num_traits::Signed;
 ///
 ///   * There are at least two coordinates in the `coordinates` array
 ///   * The coordinates are in strictly increasing order of `x` value.
+///   * At least one coordinate has a finite `x` value.
+///
+/// Coordinates with an infinite `x` value store the slope of the corresponding edge piece in
+/// their `y` value; see the module documentation for details.
 ///
 /// However, two consecutive segments do not necessarily have different slopes. These methods
 /// will panic if invariants are broken by manually editing the `coordinates` vector.
@@ -77,7 +136,6 @@ use num_traits::Signed;
 /// assert_eq!(f.y_at_x(1.25), Some(1.125));
 /// ```
 #[derive(PartialEq, Clone, Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct PiecewiseLinearFunction<T: CoordFloat> {
     /// Vector of points that make up the function.
     pub coordinates: Vec<Coord<T>>,
@@ -88,11 +146,62 @@ impl<T: CoordFloat> PiecewiseLinearFunction<T> {
     ///
     /// Returns a new PicewiseLinearFunction, or `None` if the invariants were not respected.
     pub fn new(coordinates: Vec<Coord<T>>) -> Option<Self> {
-        if coordinates.len() >= 2 && coordinates.windows(2).all(|w| w[0].x < w[1].x) {
-            Some(PiecewiseLinearFunction { coordinates })
-        } else {
-            None
+        Self::try_new(coordinates).ok()
+    }
+
+    /// Creates a new `PiecewiseLinearFunction` from a vector of `Coordinates`, returning an error
+    /// describing which invariant was not respected, if any.
+    pub fn try_new(coordinates: Vec<Coord<T>>) -> Result<Self, Error> {
+        PiecewiseLinearFunctionRef::new(&coordinates)?;
+        Ok(PiecewiseLinearFunction { coordinates })
+    }
+
+    /// Creates a new `PiecewiseLinearFunction` from a vector of finite coordinates, extended to
+    /// -inf with slope `left_slope` and to +inf with slope `right_slope` when they are specified.
+    ///
+    /// Returns `None` if the invariants were not respected, or if any of the `coordinates` is not
+    /// finite.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use piecewise_linear::{Coord, PiecewiseLinearFunction};
+    /// let f = PiecewiseLinearFunction::with_edge_slopes(
+    ///     vec![Coord { x: 0., y: 1. }],
+    ///     Some(0.),
+    ///     Some(2.),
+    /// )
+    /// .unwrap();
+    /// assert_eq!(f.domain(), (std::f64::NEG_INFINITY, std::f64::INFINITY));
+    /// assert_eq!(f.y_at_x(-10.), Some(1.));
+    /// assert_eq!(f.y_at_x(10.), Some(21.));
+    /// ```
+    pub fn with_edge_slopes(
+        coordinates: Vec<Coord<T>>,
+        left_slope: Option<T>,
+        right_slope: Option<T>,
+    ) -> Option<Self> {
+        if !coordinates
+            .iter()
+            .all(|c| c.x.is_finite() && c.y.is_finite())
+        {
+            return None;
         }
+        let mut new_coordinates = Vec::with_capacity(coordinates.len() + 2);
+        if let Some(slope) = left_slope {
+            new_coordinates.push(Coord {
+                x: T::neg_infinity(),
+                y: slope,
+            });
+        }
+        new_coordinates.extend(coordinates);
+        if let Some(slope) = right_slope {
+            new_coordinates.push(Coord {
+                x: T::infinity(),
+                y: slope,
+            });
+        }
+        PiecewiseLinearFunction::new(new_coordinates)
     }
 
     /// Returns a new constant `PiecewiseLinearFunction` with the specified domain and value.
@@ -100,7 +209,11 @@ impl<T: CoordFloat> PiecewiseLinearFunction<T> {
     /// Returns `None` if the domain is not valid (i.e. `domain.1 <= domain.0`).
     pub fn constant(domain: (T, T), value: T) -> Option<Self> {
         if domain.0 < domain.1 {
-            let coordinates = vec![(domain.0, value).into(), (domain.1, value).into()];
+            let mut coordinates = vec![edge_coordinate(domain.0, value, T::zero())];
+            if domain.0.is_infinite() && domain.1.is_infinite() {
+                coordinates.push((T::zero(), value).into());
+            }
+            coordinates.push(edge_coordinate(domain.1, value, T::zero()));
             Some(PiecewiseLinearFunction { coordinates })
         } else {
             None
@@ -109,7 +222,22 @@ impl<T: CoordFloat> PiecewiseLinearFunction<T> {
 
     /// Returns a function's domain, represented as its min and max.
     pub fn domain(&self) -> (T, T) {
-        (self.coordinates[0].x, self.coordinates.last().unwrap().x)
+        self.as_view().domain()
+    }
+
+    /// Returns whether the domain of this function is bounded, that is, whether it contains
+    /// neither -inf nor +inf.
+    pub fn is_bounded(&self) -> bool {
+        let (start, end) = self.domain();
+        start.is_finite() && end.is_finite()
+    }
+
+    /// Returns the slopes of the first and last segments of this function.
+    ///
+    /// For functions over infinite domains, these are the slopes of the pieces extending to
+    /// infinity.
+    pub fn edge_slopes(&self) -> (T, T) {
+        self.as_view().edge_slopes()
     }
 
     /// Checks whether this function has the same domain as another one.
@@ -119,9 +247,11 @@ impl<T: CoordFloat> PiecewiseLinearFunction<T> {
 
     /// Returns an iterator over the segments of f.
     ///
-    /// This iterator is guaranteed to have at least one element.
-    pub fn segments_iter(&self) -> SegmentsIterator<T> {
-        SegmentsIterator(self.coordinates.iter().peekable())
+    /// This iterator is guaranteed to have at least one element. For functions over infinite
+    /// domains, the edge segments have an infinite endpoint which stores the segment's slope (see
+    /// the module documentation).
+    pub fn segments_iter(&self) -> SegmentsIterator<'_, T> {
+        self.as_view().segments_iter()
     }
 
     /// Returns an iterator over the joint points of inflection of `self` and `other`.
@@ -131,51 +261,69 @@ impl<T: CoordFloat> PiecewiseLinearFunction<T> {
         &'a self,
         other: &'a PiecewiseLinearFunction<T>,
     ) -> Option<PointsOfInflectionIterator<'a, T>> {
-        if !self.has_same_domain_as(other) {
-            None
-        } else {
-            Some(PointsOfInflectionIterator {
-                segment_iterators: vec![
-                    self.segments_iter().peekable(),
-                    other.segments_iter().peekable(),
-                ],
-                heap: BinaryHeap::new(),
-                initial: true,
-            })
-        }
+        self.as_view().points_of_inflection_iter(other.as_view())
     }
 
     /// Returns a segment `((x1, y1), (x2, y2))` of this function such that `x1 <= x <= x2`.
     ///
     /// Returns `None` if `x` is outside the domain of f.
     pub fn segment_at_x(&self, x: T) -> Option<Line<T>> {
-        let idx = match self
-            .coordinates
-            .binary_search_by(|val| bogus_compare(&val.x, &x))
-        {
-            Ok(idx) => idx,
-            Err(idx) => {
-                if idx == 0 || idx == self.coordinates.len() {
-                    // Outside the function's domain
-                    return None;
-                } else {
-                    idx
+        self.as_view().segment_at_x(x)
+    }
+
+    /// Computes the value f(x) for this piecewise linear function.
+    ///
+    /// Returns `None` if `x` is outside the domain of f.
+    pub fn y_at_x(&self, x: T) -> Option<T> {
+        self.as_view().y_at_x(x)
+    }
+
+    /// Computes the value f(x), extrapolating outside of the domain of f.
+    ///
+    /// Inside the domain, this is the same as `y_at_x()`. Outside of it, the value is the one the
+    /// function would have after calling `expand_domain()` with the same `strategy`, but no new
+    /// function is allocated.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use piecewise_linear::{ExpandDomainStrategy, PiecewiseLinearFunction};
+    /// use std::convert::TryFrom;
+    /// let f = PiecewiseLinearFunction::try_from(vec![(0., 0.), (1., 1.), (2., 1.5)]).unwrap();
+    /// assert_eq!(f.y_at_x_extrapolated(4., ExpandDomainStrategy::ExtendSegment), 2.5);
+    /// assert_eq!(f.y_at_x_extrapolated(4., ExpandDomainStrategy::ExtendValue), 1.5);
+    /// ```
+    pub fn y_at_x_extrapolated(&self, x: T, strategy: ExpandDomainStrategy) -> T {
+        let last_index = self.coordinates.len() - 1;
+        if x < self.coordinates[0].x {
+            match strategy {
+                ExpandDomainStrategy::ExtendSegment => {
+                    y_at_x(&Line::new(self.coordinates[0], self.coordinates[1]), x)
                 }
+                ExpandDomainStrategy::ExtendValue => self.coordinates[0].y,
+            }
+        } else if x > self.coordinates[last_index].x {
+            match strategy {
+                ExpandDomainStrategy::ExtendSegment => y_at_x(
+                    &Line::new(
+                        self.coordinates[last_index - 1],
+                        self.coordinates[last_index],
+                    ),
+                    x,
+                ),
+                ExpandDomainStrategy::ExtendValue => self.coordinates[last_index].y,
             }
-        };
-
-        if idx == 0 {
-            Some(Line::new(self.coordinates[idx], self.coordinates[idx + 1]))
         } else {
-            Some(Line::new(self.coordinates[idx - 1], self.coordinates[idx]))
+            // Only reached with a NaN `x` if `y_at_x()` fails.
+            self.y_at_x(x).unwrap_or_else(T::nan)
         }
     }
 
-    /// Computes the value f(x) for this piecewise linear function.
+    /// Computes the value f(x), where `x` is first clamped to the domain of f.
     ///
-    /// Returns `None` if `x` is outside the domain of f.
-    pub fn y_at_x(&self, x: T) -> Option<T> {
-        self.segment_at_x(x).map(|line| y_at_x(&line, x))
+    /// This is equivalent to `y_at_x_extrapolated(x, ExpandDomainStrategy::ExtendValue)`.
+    pub fn y_at_x_clamped(&self, x: T) -> T {
+        self.y_at_x_extrapolated(x, ExpandDomainStrategy::ExtendValue)
     }
 
     /// Returns a new piecewise linear function that is the restriction of this function to the
@@ -221,15 +369,26 @@ impl<T: CoordFloat> PiecewiseLinearFunction<T> {
         let mut new_points = Vec::new();
         if self.coordinates[0].x > to_domain.0 {
             match &strategy {
-                ExpandDomainStrategy::ExtendSegment => new_points.push(Coord {
-                    x: to_domain.0,
-                    y: y_at_x(
-                        &Line::new(self.coordinates[0], self.coordinates[1]),
-                        to_domain.0,
-                    ),
-                }),
+                ExpandDomainStrategy::ExtendSegment => {
+                    new_points.push(Coord {
+                        x: to_domain.0,
+                        y: coordinate_y_at_x(
+                            &Line::new(self.coordinates[0], self.coordinates[1]),
+                            to_domain.0,
+                        ),
+                    });
+                    // An infinite coordinate only stores a slope, so the edge point must be kept
+                    // to anchor the function.
+                    if to_domain.0.is_infinite() {
+                        new_points.push(self.coordinates[0]);
+                    }
+                }
                 ExpandDomainStrategy::ExtendValue => {
-                    new_points.push((to_domain.0, self.coordinates[0].y).into());
+                    new_points.push(edge_coordinate(
+                        to_domain.0,
+                        self.coordinates[0].y,
+                        T::zero(),
+                    ));
                     new_points.push(self.coordinates[0]);
                 }
             }
@@ -242,19 +401,28 @@ impl<T: CoordFloat> PiecewiseLinearFunction<T> {
 
         if self.coordinates[last_index].x < to_domain.1 {
             match &strategy {
-                ExpandDomainStrategy::ExtendSegment => new_points.push(Coord {
-                    x: to_domain.1,
-                    y: y_at_x(
-                        &Line::new(
-                            self.coordinates[last_index - 1],
-                            self.coordinates[last_index],
+                ExpandDomainStrategy::ExtendSegment => {
+                    if to_domain.1.is_infinite() {
+                        new_points.push(self.coordinates[last_index]);
+                    }
+                    new_points.push(Coord {
+                        x: to_domain.1,
+                        y: coordinate_y_at_x(
+                            &Line::new(
+                                self.coordinates[last_index - 1],
+                                self.coordinates[last_index],
+                            ),
+                            to_domain.1,
                         ),
-                        to_domain.1,
-                    ),
-                }),
+                    });
+                }
                 ExpandDomainStrategy::ExtendValue => {
                     new_points.push(self.coordinates[last_index]);
-                    new_points.push((to_domain.1, self.coordinates[last_index].y).into());
+                    new_points.push(edge_coordinate(
+                        to_domain.1,
+                        self.coordinates[last_index].y,
+                        T::zero(),
+                    ));
                 }
             }
         } else {
@@ -268,18 +436,7 @@ impl<T: CoordFloat> PiecewiseLinearFunction<T> {
     ///
     /// Both functions must have the same domain; returns `None` otherwise.
     pub fn add(&self, other: &PiecewiseLinearFunction<T>) -> Option<PiecewiseLinearFunction<T>> {
-        self.points_of_inflection_iter(other).map(|poi| {
-            PiecewiseLinearFunction::new(
-                poi.map(|(x, coords)| Coord {
-                    x,
-                    y: coords[0] + coords[1],
-                })
-                .collect(),
-            )
-            // This unwrap is guaranteed to succeed as the starting POI has generates ordered x,
-            // which do not get modified.
-            .unwrap()
-        })
+        self.as_view().add(other.as_view())
     }
 
     /// Returns a new piecewise linear function that is the maximum of `self` and `other`.
@@ -302,40 +459,7 @@ impl<T: CoordFloat> PiecewiseLinearFunction<T> {
     ///
     /// Returns `None` if the domains of `self` and `other` are not equal.
     pub fn max(&self, other: &PiecewiseLinearFunction<T>) -> Option<PiecewiseLinearFunction<T>> {
-        let mut poi_iter = self.points_of_inflection_iter(other)?;
-        let mut new_values = Vec::new();
-
-        let (x, values) = poi_iter.next().unwrap();
-        let (i_largest, largest) = argmax(&values).unwrap();
-        new_values.push(Coord { x, y: *largest });
-
-        let mut prev_largest = i_largest;
-        let mut prev_x = x;
-        let mut prev_values = values;
-
-        for (x, values) in poi_iter {
-            let (i_largest, largest) = argmax(&values).unwrap();
-            if i_largest != prev_largest {
-                let (inter_x, inter_y) = line_intersect(
-                    &Line::new((prev_x, prev_values[0]), (x, values[0])),
-                    &Line::new((prev_x, prev_values[1]), (x, values[1])),
-                );
-                // This condition seems necessary as argmax() is likely unstable, so i_largest
-                // can change even if two lines remain equal.
-                if inter_x > prev_x && inter_x < x {
-                    new_values.push(Coord {
-                        x: inter_x,
-                        y: inter_y,
-                    });
-                }
-            }
-            new_values.push(Coord { x, y: *largest });
-            prev_largest = i_largest;
-            prev_x = x;
-            prev_values = values;
-        }
-
-        Some(PiecewiseLinearFunction::new(new_values).unwrap())
+        self.as_view().max(other.as_view())
     }
 }
 
@@ -385,18 +509,11 @@ impl<T: CoordFloat + Signed> ::std::ops::Neg for PiecewiseLinearFunction<T> {
 
 impl<T: CoordFloat + ::std::iter::Sum> PiecewiseLinearFunction<T> {
     /// Returns the integral of the considered function over its entire domain.
+    ///
+    /// Over an infinite domain, the integral is ±inf if it diverges, or NaN if it diverges both
+    /// ways.
     pub fn integrate(&self) -> T {
-        self.segments_iter()
-            .map(|segment| {
-                let (min_y, max_y) = if segment.start.y < segment.end.y {
-                    (segment.start.y, segment.end.y)
-                } else {
-                    (segment.end.y, segment.start.y)
-                };
-                let x_span = segment.end.x - segment.start.x;
-                x_span * (min_y + max_y / T::from(2).unwrap())
-            })
-            .sum()
+        self.as_view().integrate()
     }
 }
 
@@ -455,13 +572,14 @@ impl<T: CoordFloat> ::std::cmp::Eq for NextSegment<T> {}
 
 impl<T: CoordFloat> ::std::cmp::PartialOrd for NextSegment<T> {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        self.x.partial_cmp(&other.x).map(|r| r.reverse())
+        Some(self.cmp(other))
     }
 }
 
 impl<T: CoordFloat> ::std::cmp::Ord for NextSegment<T> {
     fn cmp(&self, other: &Self) -> Ordering {
-        bogus_compare(self, other)
+        // Reversed so that the `BinaryHeap` pops the smallest `x` first.
+        bogus_compare(&other.x, &self.x)
     }
 }
 
@@ -514,7 +632,7 @@ impl<'a, T: CoordFloat + 'a> Iterator for PointsOfInflectionIterator<'a, T> {
                 let values = self
                     .segment_iterators
                     .iter_mut()
-                    .map(|segment_iterator| y_at_x(segment_iterator.peek().unwrap(), x))
+                    .map(|segment_iterator| coordinate_y_at_x(segment_iterator.peek().unwrap(), x))
                     .collect();
 
                 while let Some(segt) = self.heap.peek().cloned() {
@@ -629,15 +747,88 @@ fn line_in_domain<T: CoordFloat>(l: &Line<T>, domain: (T, T)) -> Option<Line<T>>
     }
 }
 
+/// Returns the value of the line at `x`.
+///
+/// Infinite endpoints of `line` are interpreted as storing a slope, and the value at an infinite
+/// `x` is the limit of the line at that point. The value at a finite endpoint is exact.
 fn y_at_x<T: CoordFloat>(line: &Line<T>, x: T) -> T {
-    line.start.y + (x - line.start.x) * line.slope()
+    let slope = segment_slope(line);
+    if x == line.end.x && x.is_finite() {
+        line.end.y
+    } else if x.is_infinite() {
+        if slope == T::zero() {
+            finite_endpoint(line).y
+        } else if (slope > T::zero()) == (x > T::zero()) {
+            T::infinity()
+        } else {
+            T::neg_infinity()
+        }
+    } else if line.start.x.is_finite() {
+        line.start.y + (x - line.start.x) * slope
+    } else {
+        line.end.y + (x - line.end.x) * slope
+    }
+}
+
+/// Returns the `y` value of the coordinate at `x` on `line`. This is the same as `y_at_x()`, except
+/// that the slope is returned for infinite `x` values.
+fn coordinate_y_at_x<T: CoordFloat>(line: &Line<T>, x: T) -> T {
+    if x.is_infinite() {
+        segment_slope(line)
+    } else {
+        y_at_x(line, x)
+    }
+}
+
+/// Returns the slope of a segment, which may have an infinite endpoint storing the slope.
+fn segment_slope<T: CoordFloat>(line: &Line<T>) -> T {
+    if line.end.x.is_infinite() {
+        line.end.y
+    } else if line.start.x.is_infinite() {
+        line.start.y
+    } else {
+        line.slope()
+    }
+}
+
+/// Returns an endpoint of the segment with a finite `x` value.
+fn finite_endpoint<T: CoordFloat>(line: &Line<T>) -> Coord<T> {
+    if line.start.x.is_infinite() {
+        line.end
+    } else {
+        line.start
+    }
+}
+
+/// Returns the coordinate for `x` in a function which has the value `y` at `x` if `x` is finite,
+/// or the slope `slope` if `x` is infinite.
+fn edge_coordinate<T: CoordFloat>(x: T, y: T, slope: T) -> Coord<T> {
+    if x.is_infinite() {
+        Coord { x, y: slope }
+    } else {
+        Coord { x, y }
+    }
+}
+
+/// Returns the integral over [x, inf[ of a line which has the value `y` at `x` and the slope
+/// `slope`.
+fn edge_integral<T: CoordFloat>(y: T, slope: T) -> T {
+    if slope > T::zero() || (slope == T::zero() && y > T::zero()) {
+        T::infinity()
+    } else if slope < T::zero() || (slope == T::zero() && y < T::zero()) {
+        T::neg_infinity()
+    } else {
+        T::zero()
+    }
 }
 
 fn line_intersect<T: CoordFloat>(l1: &Line<T>, l2: &Line<T>) -> (T, T) {
-    let y_intercept_1 = l1.start.y - l1.start.x * l1.slope();
-    let y_intercept_2 = l2.start.y - l2.start.x * l2.slope();
+    let (anchor_1, slope_1) = (finite_endpoint(l1), segment_slope(l1));
+    let (anchor_2, slope_2) = (finite_endpoint(l2), segment_slope(l2));
+    let y_intercept_1 = anchor_1.y - anchor_1.x * slope_1;
+    let y_intercept_2 = anchor_2.y - anchor_2.x * slope_2;
 
-    let x_intersect = (y_intercept_2 - y_intercept_1) / (l1.slope() - l2.slope());
+    let x_intersect = (y_intercept_2 - y_intercept_1) / (slope_1 - slope_2);
     let y_intersect = y_at_x(l1, x_intersect);
     (x_intersect, y_intersect)
 }
@@ -665,7 +856,16 @@ fn argmax<T: CoordFloat>(values: &[T]) -> Option<(usize, &T)> {
         .max_by(|(_, a), (_, b)| bogus_compare(a, b))
 }
 
+fn argmin<T: CoordFloat>(values: &[T]) -> Option<(usize, &T)> {
+    values
+        .iter()
+        .enumerate()
+        .min_by(|(_, a), (_, b)| bogus_compare(a, b))
+}
+
 #[cfg(test)]
+// The tests predating the associated float constants still use the `std::f64` ones.
+#[allow(clippy::legacy_numeric_constants)]
 mod tests {
     use std::convert::TryInto;
 
@@ -680,11 +880,16 @@ mod tests {
             (1., 2.),
             (2., 3.),
             (3., 4.),
-            (std::f64::INFINITY, std::f64::NEG_INFINITY),
         ])
         .unwrap()
     }
 
+    /// The test function, extended to +inf with a slope of -1.
+    fn get_half_infinite_test_function() -> PiecewiseLinearFunction<f64> {
+        PiecewiseLinearFunction::with_edge_slopes(get_test_function().coordinates, None, Some(-1.))
+            .unwrap()
+    }
+
     #[test]
     fn test_y_at_x() {
         assert_eq!(y_at_x(&Line::new((0., 0.), (1., 1.)), 0.25), 0.25);
@@ -760,6 +965,36 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_y_at_x_extrapolated() {
+        let f = PiecewiseLinearFunction::try_from(vec![(0., 0.), (1., 1.), (2., 1.5)]).unwrap();
+        for &strategy in &[
+            ExpandDomainStrategy::ExtendSegment,
+            ExpandDomainStrategy::ExtendValue,
+        ] {
+            assert_eq!(f.y_at_x_extrapolated(1.5, strategy), 1.25);
+            assert_eq!(f.y_at_x_extrapolated(2., strategy), 1.5);
+            for &x in &[-3., -0.5, 2.5, 10f64] {
+                let expanded = f.expand_domain((x.min(0.), x.max(2.)), strategy);
+                assert_eq!(
+                    f.y_at_x_extrapolated(x, strategy),
+                    expanded.y_at_x(x).unwrap()
+                );
+            }
+        }
+        assert!(f
+            .y_at_x_extrapolated(f64::NAN, ExpandDomainStrategy::ExtendSegment)
+            .is_nan());
+    }
+
+    #[test]
+    fn test_y_at_x_clamped() {
+        let f = PiecewiseLinearFunction::try_from(vec![(0., 0.), (1., 1.), (2., 1.5)]).unwrap();
+        assert_eq!(f.y_at_x_clamped(-1.), 0.);
+        assert_eq!(f.y_at_x_clamped(0.5), 0.5);
+        assert_eq!(f.y_at_x_clamped(f64::INFINITY), 1.5);
+    }
+
     #[test]
     fn test_line_in_domain() {
         // Case 1 - fully outside
@@ -811,7 +1046,7 @@ mod tests {
             0.,
         );
         assert_eq!(
-            get_test_function()
+            get_half_infinite_test_function()
                 .shrink_domain((0.0, std::f64::INFINITY))
                 .unwrap(),
             PiecewiseLinearFunction::try_from(vec![
@@ -820,7 +1055,7 @@ mod tests {
                 (1., 2.),
                 (2., 3.),
                 (3., 4.),
-                (std::f64::INFINITY, std::f64::NEG_INFINITY),
+                (std::f64::INFINITY, -1.),
             ])
             .unwrap()
         );
@@ -870,6 +1105,212 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_integrate() {
+        let f = PiecewiseLinearFunction::try_from(vec![(0., 0.), (1., 1.), (2., 1.5)]).unwrap();
+        assert_eq!(f.integrate(), 1.75);
+        let g = PiecewiseLinearFunction::try_from(vec![(0., 2.), (1., -2.)]).unwrap();
+        assert_eq!(g.integrate(), 0.);
+    }
+
+    /// |x|, over all real numbers.
+    fn get_infinite_abs_function() -> PiecewiseLinearFunction<f64> {
+        PiecewiseLinearFunction::with_edge_slopes(vec![(0., 0.).into()], Some(-1.), Some(1.))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_infinite_new() {
+        assert_eq!(
+            PiecewiseLinearFunction::try_from(vec![(f64::NEG_INFINITY, 0.), (f64::INFINITY, 1.)]),
+            Err(())
+        );
+        assert_eq!(
+            PiecewiseLinearFunction::with_edge_slopes(
+                vec![(f64::NEG_INFINITY, 0.).into(), (0., 1.).into()],
+                None,
+                Some(1.)
+            ),
+            None
+        );
+        assert_eq!(
+            PiecewiseLinearFunction::<f64>::with_edge_slopes(vec![], Some(1.), Some(1.)),
+            None
+        );
+        assert_eq!(
+            get_infinite_abs_function(),
+            vec![(f64::NEG_INFINITY, -1.), (0., 0.), (f64::INFINITY, 1.)]
+                .try_into()
+                .unwrap()
+        );
+        assert_eq!(
+            PiecewiseLinearFunction::constant((f64::NEG_INFINITY, f64::INFINITY), 3.).unwrap(),
+            vec![(f64::NEG_INFINITY, 0.), (0., 3.), (f64::INFINITY, 0.)]
+                .try_into()
+                .unwrap()
+        );
+        assert_eq!(
+            PiecewiseLinearFunction::constant((1., f64::INFINITY), 3.).unwrap(),
+            vec![(1., 3.), (f64::INFINITY, 0.)].try_into().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_infinite_y_at_x() {
+        let f = get_infinite_abs_function();
+        assert!(!f.is_bounded());
+        assert_eq!(f.edge_slopes(), (-1., 1.));
+        assert_eq!(f.y_at_x(-3.), Some(3.));
+        assert_eq!(f.y_at_x(0.), Some(0.));
+        assert_eq!(f.y_at_x(2.5), Some(2.5));
+        assert_eq!(f.y_at_x(f64::INFINITY), Some(f64::INFINITY));
+        assert_eq!(f.y_at_x(f64::NEG_INFINITY), Some(f64::INFINITY));
+
+        let g = PiecewiseLinearFunction::constant((f64::NEG_INFINITY, 0.), 2.).unwrap();
+        assert_eq!(g.y_at_x(-1e300), Some(2.));
+        assert_eq!(g.y_at_x(f64::NEG_INFINITY), Some(2.));
+        assert_eq!(g.y_at_x(1.), None);
+        assert_eq!(
+            g.y_at_x_extrapolated(1., ExpandDomainStrategy::ExtendSegment),
+            2.
+        );
+        assert_eq!(f.y_at_x_clamped(4.), 4.);
+    }
+
+    #[test]
+    fn test_infinite_integrate() {
+        let zero_tails = PiecewiseLinearFunction::with_edge_slopes(
+            vec![(-1., 0.).into(), (0., 1.).into(), (1., 0.).into()],
+            Some(0.),
+            Some(0.),
+        )
+        .unwrap();
+        assert_eq!(zero_tails.integrate(), 1.);
+        assert_eq!(get_infinite_abs_function().integrate(), f64::INFINITY);
+        assert_eq!(
+            get_infinite_abs_function().negate().integrate(),
+            f64::NEG_INFINITY
+        );
+        let left_only =
+            PiecewiseLinearFunction::try_from(vec![(f64::NEG_INFINITY, 1.), (0., 0.)]).unwrap();
+        assert_eq!(left_only.integrate(), f64::NEG_INFINITY);
+        let constant = PiecewiseLinearFunction::constant((2., f64::INFINITY), 1.).unwrap();
+        assert_eq!(constant.integrate(), f64::INFINITY);
+        let both_ways =
+            PiecewiseLinearFunction::with_edge_slopes(vec![(0f64, 0.).into()], Some(1.), Some(1.))
+                .unwrap();
+        assert!(both_ways.integrate().is_nan());
+    }
+
+    #[test]
+    fn test_infinite_max_min() {
+        let f = get_infinite_abs_function();
+        let g = PiecewiseLinearFunction::constant((f64::NEG_INFINITY, f64::INFINITY), 1.).unwrap();
+        let max = f.max(&g).unwrap();
+        assert_eq!(
+            max,
+            vec![
+                (f64::NEG_INFINITY, -1.),
+                (-1., 1.),
+                (0., 1.),
+                (1., 1.),
+                (f64::INFINITY, 1.)
+            ]
+            .try_into()
+            .unwrap()
+        );
+        let min = f.min(&g).unwrap();
+        assert_eq!(
+            min,
+            vec![
+                (f64::NEG_INFINITY, 0.),
+                (-1., 1.),
+                (0., 0.),
+                (1., 1.),
+                (f64::INFINITY, 0.)
+            ]
+            .try_into()
+            .unwrap()
+        );
+        for &x in &[-1e6, -3., -0.5, 0., 0.25, 7.] {
+            let (fx, gx) = (f.y_at_x(x).unwrap(), g.y_at_x(x).unwrap());
+            assert_eq!(max.y_at_x(x).unwrap(), fx.max(gx));
+            assert_eq!(min.y_at_x(x).unwrap(), fx.min(gx));
+        }
+        assert_eq!(
+            f.add(&g).unwrap(),
+            vec![(f64::NEG_INFINITY, -1.), (0., 1.), (f64::INFINITY, 1.)]
+                .try_into()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_infinite_shrink_domain() {
+        let f = get_infinite_abs_function()
+            .min(
+                &PiecewiseLinearFunction::constant((f64::NEG_INFINITY, f64::INFINITY), 1.).unwrap(),
+            )
+            .unwrap();
+        assert_eq!(
+            f.shrink_domain((-2., f64::INFINITY)).unwrap(),
+            vec![
+                (-2., 1.),
+                (-1., 1.),
+                (0., 0.),
+                (1., 1.),
+                (f64::INFINITY, 0.)
+            ]
+            .try_into()
+            .unwrap()
+        );
+        assert_eq!(
+            f.shrink_domain((f64::NEG_INFINITY, 0.5)).unwrap(),
+            vec![(f64::NEG_INFINITY, 0.), (-1., 1.), (0., 0.), (0.5, 0.5)]
+                .try_into()
+                .unwrap()
+        );
+        assert_eq!(
+            get_infinite_abs_function().shrink_domain((3., 4.)).unwrap(),
+            vec![(3., 3.), (4., 4.)].try_into().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_infinite_expand_domain() {
+        let f = PiecewiseLinearFunction::try_from(vec![(0., 0.), (1., 2.)]).unwrap();
+        let everywhere = (f64::NEG_INFINITY, f64::INFINITY);
+        assert_eq!(
+            f.expand_domain(everywhere, ExpandDomainStrategy::ExtendSegment),
+            vec![
+                (f64::NEG_INFINITY, 2.),
+                (0., 0.),
+                (1., 2.),
+                (f64::INFINITY, 2.)
+            ]
+            .try_into()
+            .unwrap()
+        );
+        let expanded = f.expand_domain(everywhere, ExpandDomainStrategy::ExtendValue);
+        assert_eq!(
+            expanded,
+            vec![
+                (f64::NEG_INFINITY, 0.),
+                (0., 0.),
+                (1., 2.),
+                (f64::INFINITY, 0.)
+            ]
+            .try_into()
+            .unwrap()
+        );
+        assert_eq!(expanded.y_at_x(-5.), Some(0.));
+        assert_eq!(expanded.y_at_x(f64::INFINITY), Some(2.));
+        assert_eq!(
+            expanded.expand_domain(everywhere, ExpandDomainStrategy::ExtendSegment),
+            expanded
+        );
+    }
+
     #[test]
     fn test_line_intersect() {
         assert_eq!(
@@ -0,0 +1,653 @@
+// Copyright 2019 Matthieu Felix
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Encodings of piecewise linear functions as linear and mixed-integer linear programs.
+//!
+//! An `LpModel` is a solver-agnostic list of variables, rows and special ordered sets, which can
+//! be written in the LP and MPS text formats understood by most solvers. Piecewise linear
+//! functions are added to a model with `LpModel::add_function()`, which links two existing
+//! variables `x` and `y` so that `y = f(x)`, or `y >= f(x)` for the epigraph formulations.
+//!
+//! ## Example
+//!
+//! ```
+//! use piecewise_linear::lp::{Formulation, LpModel, VariableKind};
+//! use piecewise_linear::PiecewiseLinearFunction;
+//! use std::convert::TryFrom;
+//! let cost = PiecewiseLinearFunction::try_from(vec![(0., 0.), (10., 5.), (20., 20.)]).unwrap();
+//! let mut model = LpModel::new();
+//! let x = model.add_variable("power", 0., 20., VariableKind::Continuous);
+//! let y = model.add_variable("cost", f64::NEG_INFINITY, f64::INFINITY, VariableKind::Continuous);
+//! model.add_function(&cost, x, y, Formulation::Lambda).unwrap();
+//! model.objective.push((y, 1.));
+//! let mut lp = Vec::new();
+//! model.write_lp(&mut lp).unwrap();
+//! assert!(String::from_utf8(lp).unwrap().starts_with("Minimize\n obj: + 1 cost\n"));
+//! ```
+
+use std::fmt::Display;
+use std::io;
+
+use crate::{CoordFloat, Error, PiecewiseLinearFunction};
+
+/// The type of a variable.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum VariableKind {
+    /// A real variable.
+    Continuous,
+    /// A variable which is either 0 or 1. Its bounds are ignored.
+    Binary,
+}
+
+/// A variable of a model, with its bounds, which may be infinite.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Variable<T: CoordFloat> {
+    pub name: String,
+    pub lower: T,
+    pub upper: T,
+    pub kind: VariableKind,
+}
+
+/// The comparison between the two sides of a row.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Sense {
+    LessOrEqual,
+    GreaterOrEqual,
+    Equal,
+}
+
+/// A linear constraint `terms <sense> rhs`. Each term is a pair of a variable index and its
+/// coefficient.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Row<T: CoordFloat> {
+    pub name: String,
+    pub terms: Vec<(usize, T)>,
+    pub sense: Sense,
+    pub rhs: T,
+}
+
+/// A special ordered set of type 2: at most two of its variables may be non-zero, and they must
+/// be adjacent. Each member is a pair of a variable index and its weight, which sets the order.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sos2<T: CoordFloat> {
+    pub name: String,
+    pub members: Vec<(usize, T)>,
+}
+
+/// How a piecewise linear function is encoded by `LpModel::add_function()`.
+///
+/// With `n` points, the formulations add the following variables:
+///
+/// | Formulation                    | Relation   | Continuous | Binary  | Function   |
+/// |--------------------------------|------------|------------|---------|------------|
+/// | `Lambda`                       | y >= f(x)  | n          | 0       | convex     |
+/// | `Incremental`                  | y >= f(x)  | n - 1      | 0       | convex     |
+/// | `Sos2`                         | y = f(x)   | n          | 0       | any        |
+/// | `DisjunctiveConvexCombination` | y = f(x)   | 2 (n - 1)  | n - 1   | any        |
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Formulation {
+    /// The epigraph of a convex function, as the convex combinations of its points: x = Σ λi xi,
+    /// y >= Σ λi yi and Σ λi = 1.
+    Lambda,
+    /// The epigraph of a convex function, as the sum of fractions of its segments:
+    /// x = x0 + Σ δi (xi - xi-1) and y >= y0 + Σ δi (yi - yi-1), with 0 <= δi <= 1.
+    Incremental,
+    /// The graph of any function, as the lambda formulation with an equality for `y`, and the
+    /// λi in a special ordered set of type 2.
+    Sos2,
+    /// The graph of any function, as a choice of one segment with a binary variable zi per
+    /// segment, and the convex combination of its endpoints: x = Σ (μi xi + νi xi+1),
+    /// y = Σ (μi yi + νi yi+1), μi + νi = zi and Σ zi = 1.
+    DisjunctiveConvexCombination,
+}
+
+/// A solver-agnostic linear or mixed-integer linear program, which minimizes `objective`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LpModel<T: CoordFloat> {
+    pub variables: Vec<Variable<T>>,
+    pub rows: Vec<Row<T>>,
+    pub sos2: Vec<Sos2<T>>,
+    /// The terms of the objective function, which is minimized. Negate them to maximize.
+    pub objective: Vec<(usize, T)>,
+}
+
+impl<T: CoordFloat> Default for LpModel<T> {
+    fn default() -> Self {
+        LpModel {
+            variables: Vec::new(),
+            rows: Vec::new(),
+            sos2: Vec::new(),
+            objective: Vec::new(),
+        }
+    }
+}
+
+impl<T: CoordFloat> LpModel<T> {
+    /// Creates an empty model.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a variable and returns its index.
+    pub fn add_variable(&mut self, name: &str, lower: T, upper: T, kind: VariableKind) -> usize {
+        self.variables.push(Variable {
+            name: name.to_string(),
+            lower,
+            upper,
+            kind,
+        });
+        self.variables.len() - 1
+    }
+
+    /// Adds a row and returns its index. Terms with a zero coefficient are dropped.
+    pub fn add_row(&mut self, name: &str, terms: Vec<(usize, T)>, sense: Sense, rhs: T) -> usize {
+        self.rows.push(Row {
+            name: name.to_string(),
+            terms: terms.into_iter().filter(|&(_, c)| c != T::zero()).collect(),
+            sense,
+            rhs,
+        });
+        self.rows.len() - 1
+    }
+
+    /// Adds the variables and rows linking the variables with indices `x` and `y` through `f`,
+    /// using `formulation`. The names of the new variables and rows are prefixed by the name of
+    /// `y`.
+    ///
+    /// This does not change the bounds of `x`: it is implicitly bounded by the domain of `f`.
+    ///
+    /// Returns `Error::UnboundedDomain` if the domain of `f` is not bounded,
+    /// `Error::NotConvex` if the formulation encodes an epigraph and `f` is not convex,
+    /// `Error::UnknownVariable` if `x` or `y` is not the index of a variable, and
+    /// `Error::DuplicateName` if the names of the new rows are already used, for instance because
+    /// a function was already added for `y`.
+    pub fn add_function(
+        &mut self,
+        f: &PiecewiseLinearFunction<T>,
+        x: usize,
+        y: usize,
+        formulation: Formulation,
+    ) -> Result<(), Error> {
+        if !f.is_bounded() {
+            return Err(Error::UnboundedDomain);
+        }
+        if let Some(&index) = [x, y].iter().find(|&&i| i >= self.variables.len()) {
+            return Err(Error::UnknownVariable(index));
+        }
+        let epigraph = match formulation {
+            Formulation::Lambda | Formulation::Incremental => true,
+            Formulation::Sos2 | Formulation::DisjunctiveConvexCombination => false,
+        };
+        if epigraph && !f.is_convex() {
+            return Err(Error::NotConvex);
+        }
+        let y_sense = if epigraph {
+            Sense::GreaterOrEqual
+        } else {
+            Sense::Equal
+        };
+        let prefix = self.variables[y].name.clone();
+        // Every formulation adds these rows, so the other names are free if they are.
+        for name in &[format!("{}_x", prefix), format!("{}_y", prefix)] {
+            if self.rows.iter().any(|row| &row.name == name) {
+                return Err(Error::DuplicateName(name.clone()));
+            }
+        }
+        let points = &f.coordinates;
+        let (zero, one) = (T::zero(), T::one());
+
+        match formulation {
+            Formulation::Lambda | Formulation::Sos2 => {
+                let lambdas: Vec<usize> = (0..points.len())
+                    .map(|i| {
+                        let name = format!("{}_lambda_{}", prefix, i);
+                        self.add_variable(&name, zero, one, VariableKind::Continuous)
+                    })
+                    .collect();
+                self.add_row(
+                    &format!("{}_convexity", prefix),
+                    lambdas.iter().map(|&l| (l, one)).collect(),
+                    Sense::Equal,
+                    one,
+                );
+                let x_terms = lambdas.iter().zip(points).map(|(&l, p)| (l, -p.x));
+                let y_terms = lambdas.iter().zip(points).map(|(&l, p)| (l, -p.y));
+                self.add_row(
+                    &format!("{}_x", prefix),
+                    Some((x, one)).into_iter().chain(x_terms).collect(),
+                    Sense::Equal,
+                    zero,
+                );
+                self.add_row(
+                    &format!("{}_y", prefix),
+                    Some((y, one)).into_iter().chain(y_terms).collect(),
+                    y_sense,
+                    zero,
+                );
+                if formulation == Formulation::Sos2 {
+                    self.sos2.push(Sos2 {
+                        name: format!("{}_sos2", prefix),
+                        members: lambdas
+                            .iter()
+                            .enumerate()
+                            .map(|(i, &l)| (l, T::from(i + 1).unwrap()))
+                            .collect(),
+                    });
+                }
+            }
+            Formulation::Incremental => {
+                let deltas: Vec<usize> = (1..points.len())
+                    .map(|i| {
+                        let name = format!("{}_delta_{}", prefix, i);
+                        self.add_variable(&name, zero, one, VariableKind::Continuous)
+                    })
+                    .collect();
+                let steps = deltas.iter().zip(points.windows(2));
+                let x_terms = steps.clone().map(|(&d, w)| (d, w[0].x - w[1].x));
+                let y_terms = steps.map(|(&d, w)| (d, w[0].y - w[1].y));
+                self.add_row(
+                    &format!("{}_x", prefix),
+                    Some((x, one)).into_iter().chain(x_terms).collect(),
+                    Sense::Equal,
+                    points[0].x,
+                );
+                self.add_row(
+                    &format!("{}_y", prefix),
+                    Some((y, one)).into_iter().chain(y_terms).collect(),
+                    y_sense,
+                    points[0].y,
+                );
+            }
+            Formulation::DisjunctiveConvexCombination => {
+                let mut choice = Vec::with_capacity(points.len() - 1);
+                let mut x_terms = vec![(x, one)];
+                let mut y_terms = vec![(y, one)];
+                for (i, w) in points.windows(2).enumerate() {
+                    let z = self.add_variable(
+                        &format!("{}_z_{}", prefix, i),
+                        zero,
+                        one,
+                        VariableKind::Binary,
+                    );
+                    let mu = self.add_variable(
+                        &format!("{}_mu_{}", prefix, i),
+                        zero,
+                        one,
+                        VariableKind::Continuous,
+                    );
+                    let nu = self.add_variable(
+                        &format!("{}_nu_{}", prefix, i),
+                        zero,
+                        one,
+                        VariableKind::Continuous,
+                    );
+                    self.add_row(
+                        &format!("{}_segment_{}", prefix, i),
+                        vec![(mu, one), (nu, one), (z, -one)],
+                        Sense::Equal,
+                        zero,
+                    );
+                    choice.push((z, one));
+                    x_terms.extend(vec![(mu, -w[0].x), (nu, -w[1].x)]);
+                    y_terms.extend(vec![(mu, -w[0].y), (nu, -w[1].y)]);
+                }
+                self.add_row(&format!("{}_choice", prefix), choice, Sense::Equal, one);
+                self.add_row(&format!("{}_x", prefix), x_terms, Sense::Equal, zero);
+                self.add_row(&format!("{}_y", prefix), y_terms, y_sense, zero);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<T: CoordFloat + Display> LpModel<T> {
+    /// Writes the model in the CPLEX LP format.
+    pub fn write_lp<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        writeln!(writer, "Minimize")?;
+        write!(writer, " obj:")?;
+        if self.objective.is_empty() && !self.variables.is_empty() {
+            write!(writer, " 0 {}", self.variables[0].name)?;
+        }
+        self.write_lp_terms(writer, &self.objective)?;
+        writeln!(writer)?;
+
+        writeln!(writer, "Subject To")?;
+        for row in &self.rows {
+            write!(writer, " {}:", row.name)?;
+            self.write_lp_terms(writer, &row.terms)?;
+            let sense = match row.sense {
+                Sense::LessOrEqual => "<=",
+                Sense::GreaterOrEqual => ">=",
+                Sense::Equal => "=",
+            };
+            writeln!(writer, " {} {}", sense, row.rhs)?;
+        }
+
+        writeln!(writer, "Bounds")?;
+        for variable in &self.variables {
+            if variable.kind == VariableKind::Binary {
+                continue;
+            }
+            if variable.lower.is_infinite() && variable.upper.is_infinite() {
+                writeln!(writer, " {} free", variable.name)?;
+            } else {
+                writeln!(
+                    writer,
+                    " {} <= {} <= {}",
+                    lp_number(variable.lower),
+                    variable.name,
+                    lp_number(variable.upper)
+                )?;
+            }
+        }
+
+        let binaries: Vec<&Variable<T>> = self
+            .variables
+            .iter()
+            .filter(|v| v.kind == VariableKind::Binary)
+            .collect();
+        if !binaries.is_empty() {
+            writeln!(writer, "Binaries")?;
+            for variable in binaries {
+                writeln!(writer, " {}", variable.name)?;
+            }
+        }
+
+        if !self.sos2.is_empty() {
+            writeln!(writer, "SOS")?;
+            for set in &self.sos2 {
+                write!(writer, " {}: S2::", set.name)?;
+                for &(variable, weight) in &set.members {
+                    write!(writer, " {}:{}", self.variables[variable].name, weight)?;
+                }
+                writeln!(writer)?;
+            }
+        }
+        writeln!(writer, "End")
+    }
+
+    fn write_lp_terms<W: io::Write>(&self, writer: &mut W, terms: &[(usize, T)]) -> io::Result<()> {
+        for &(variable, coefficient) in terms {
+            let sign = if coefficient < T::zero() { '-' } else { '+' };
+            write!(
+                writer,
+                " {} {} {}",
+                sign,
+                coefficient.abs(),
+                self.variables[variable].name
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Writes the model in the free MPS format, where fields are separated by spaces.
+    ///
+    /// Binary variables use the `BV` bound type, and special ordered sets are written in an `SOS`
+    /// section, whose entries are `S2 SOS <name> <priority>` followed by `<name> <variable>
+    /// <weight>` lines.
+    pub fn write_mps<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        writeln!(writer, "NAME")?;
+        writeln!(writer, "ROWS")?;
+        writeln!(writer, " N  obj")?;
+        for row in &self.rows {
+            let sense = match row.sense {
+                Sense::LessOrEqual => 'L',
+                Sense::GreaterOrEqual => 'G',
+                Sense::Equal => 'E',
+            };
+            writeln!(writer, " {}  {}", sense, row.name)?;
+        }
+
+        // The MPS format lists the coefficients column by column.
+        let mut columns: Vec<Vec<(&str, T)>> = vec![Vec::new(); self.variables.len()];
+        for &(variable, coefficient) in &self.objective {
+            columns[variable].push(("obj", coefficient));
+        }
+        for row in &self.rows {
+            for &(variable, coefficient) in &row.terms {
+                columns[variable].push((&row.name, coefficient));
+            }
+        }
+        writeln!(writer, "COLUMNS")?;
+        for (variable, column) in self.variables.iter().zip(columns) {
+            for (row, coefficient) in column {
+                writeln!(writer, "    {}  {}  {}", variable.name, row, coefficient)?;
+            }
+        }
+
+        writeln!(writer, "RHS")?;
+        for row in self.rows.iter().filter(|r| r.rhs != T::zero()) {
+            writeln!(writer, "    RHS  {}  {}", row.name, row.rhs)?;
+        }
+
+        writeln!(writer, "BOUNDS")?;
+        for variable in &self.variables {
+            let name = &variable.name;
+            let (lower, upper) = (variable.lower, variable.upper);
+            if variable.kind == VariableKind::Binary {
+                writeln!(writer, " BV BND  {}", name)?;
+            } else if lower.is_infinite() && upper.is_infinite() {
+                writeln!(writer, " FR BND  {}", name)?;
+            } else if lower == upper {
+                writeln!(writer, " FX BND  {}  {}", name, lower)?;
+            } else {
+                if lower.is_infinite() {
+                    writeln!(writer, " MI BND  {}", name)?;
+                } else if lower != T::zero() {
+                    writeln!(writer, " LO BND  {}  {}", name, lower)?;
+                }
+                if upper.is_finite() {
+                    writeln!(writer, " UP BND  {}  {}", name, upper)?;
+                }
+            }
+        }
+
+        if !self.sos2.is_empty() {
+            writeln!(writer, "SOS")?;
+            for set in &self.sos2 {
+                writeln!(writer, " S2 SOS  {}  1", set.name)?;
+                for &(variable, weight) in &set.members {
+                    writeln!(
+                        writer,
+                        "    {}  {}  {}",
+                        set.name, self.variables[variable].name, weight
+                    )?;
+                }
+            }
+        }
+        writeln!(writer, "ENDATA")
+    }
+}
+
+/// Formats a bound for the LP format, which spells infinite values `-inf` and `+inf`.
+fn lp_number<T: CoordFloat + Display>(value: T) -> String {
+    if value.is_infinite() {
+        if value > T::zero() { "+inf" } else { "-inf" }.to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use super::*;
+
+    /// Returns a model with variables `x` and `y` linked by `f`.
+    fn get_model(
+        f: &PiecewiseLinearFunction<f64>,
+        formulation: Formulation,
+    ) -> Result<LpModel<f64>, Error> {
+        let mut model = LpModel::new();
+        let (start, end) = f.domain();
+        let x = model.add_variable("x", start, end, VariableKind::Continuous);
+        let y = model.add_variable(
+            "y",
+            f64::NEG_INFINITY,
+            f64::INFINITY,
+            VariableKind::Continuous,
+        );
+        model.add_function(f, x, y, formulation)?;
+        Ok(model)
+    }
+
+    /// Checks whether `values` satisfies all rows and bounds of `model`, ignoring SOS2 sets.
+    fn is_feasible(model: &LpModel<f64>, values: &[f64]) -> bool {
+        let bounds = model.variables.iter().zip(values).all(|(v, &value)| {
+            let integral = v.kind == VariableKind::Continuous || value == 0. || value == 1.;
+            integral && value >= v.lower - 1e-9 && value <= v.upper + 1e-9
+        });
+        bounds
+            && model.rows.iter().all(|row| {
+                let lhs: f64 = row.terms.iter().map(|&(v, c)| c * values[v]).sum();
+                match row.sense {
+                    Sense::LessOrEqual => lhs <= row.rhs + 1e-9,
+                    Sense::GreaterOrEqual => lhs >= row.rhs - 1e-9,
+                    Sense::Equal => (lhs - row.rhs).abs() <= 1e-9,
+                }
+            })
+    }
+
+    #[test]
+    fn test_epigraph_formulations() {
+        let f = PiecewiseLinearFunction::try_from(vec![(0., 2.), (1., 0.), (3., 1.)]).unwrap();
+        let lambda = get_model(&f, Formulation::Lambda).unwrap();
+        assert_eq!(lambda.variables.len(), 2 + 3);
+        assert_eq!(lambda.rows.len(), 3);
+        // x = 2, y = f(2) = 0.5, as the midpoint of the last two points.
+        assert!(is_feasible(&lambda, &[2., 0.5, 0., 0.5, 0.5]));
+        assert!(is_feasible(&lambda, &[2., 1., 0., 0.5, 0.5]));
+        assert!(!is_feasible(&lambda, &[2., 0.25, 0., 0.5, 0.5]));
+
+        let incremental = get_model(&f, Formulation::Incremental).unwrap();
+        assert_eq!(incremental.variables.len(), 2 + 2);
+        assert!(is_feasible(&incremental, &[2., 0.5, 1., 0.5]));
+        assert!(!is_feasible(&incremental, &[2., 0.4, 1., 0.5]));
+
+        let concave = f.negate();
+        assert_eq!(
+            get_model(&concave, Formulation::Lambda),
+            Err(Error::NotConvex)
+        );
+        let unbounded = PiecewiseLinearFunction::constant((0., f64::INFINITY), 1.).unwrap();
+        assert_eq!(
+            get_model(&unbounded, Formulation::Sos2),
+            Err(Error::UnboundedDomain)
+        );
+    }
+
+    #[test]
+    fn test_graph_formulations() {
+        let f = PiecewiseLinearFunction::try_from(vec![(0., 0.), (1., 2.), (3., 1.)]).unwrap();
+        let sos2 = get_model(&f, Formulation::Sos2).unwrap();
+        assert_eq!(sos2.sos2.len(), 1);
+        assert_eq!(sos2.sos2[0].members, vec![(2, 1.), (3, 2.), (4, 3.)]);
+        assert!(is_feasible(&sos2, &[2., 1.5, 0., 0.5, 0.5]));
+        assert!(!is_feasible(&sos2, &[2., 2., 0., 0.5, 0.5]));
+
+        let dcc = get_model(&f, Formulation::DisjunctiveConvexCombination).unwrap();
+        // Variables z, mu and nu for each segment.
+        assert_eq!(dcc.variables.len(), 2 + 6);
+        assert!(is_feasible(&dcc, &[2., 1.5, 0., 0., 0., 1., 0.5, 0.5]));
+        assert!(!is_feasible(&dcc, &[2., 1.5, 0.5, 0.5, 0., 0.5, 0., 0.5]));
+    }
+
+    #[test]
+    fn test_invalid_arguments() {
+        let f = PiecewiseLinearFunction::try_from(vec![(0., 0.), (1., 2.), (3., 1.)]).unwrap();
+        let mut model = get_model(&f, Formulation::Sos2).unwrap();
+        let size = model.variables.len();
+        assert_eq!(
+            model.add_function(&f, 0, size, Formulation::Sos2),
+            Err(Error::UnknownVariable(size))
+        );
+        assert_eq!(
+            model.add_function(&f, size + 1, 1, Formulation::Sos2),
+            Err(Error::UnknownVariable(size + 1))
+        );
+        assert_eq!(
+            model.add_function(&f, 0, 1, Formulation::DisjunctiveConvexCombination),
+            Err(Error::DuplicateName("y_x".to_string()))
+        );
+        assert_eq!(model.variables.len(), size);
+    }
+
+    #[test]
+    fn test_write() {
+        let f = PiecewiseLinearFunction::try_from(vec![(0., 0.), (1., 2.), (3., 1.)]).unwrap();
+        let mut model = get_model(&f, Formulation::Sos2).unwrap();
+        model.objective.push((1, -1.));
+
+        let mut lp = Vec::new();
+        model.write_lp(&mut lp).unwrap();
+        assert_eq!(
+            String::from_utf8(lp).unwrap(),
+            "Minimize
+ obj: - 1 y
+Subject To
+ y_convexity: + 1 y_lambda_0 + 1 y_lambda_1 + 1 y_lambda_2 = 1
+ y_x: + 1 x - 1 y_lambda_1 - 3 y_lambda_2 = 0
+ y_y: + 1 y - 2 y_lambda_1 - 1 y_lambda_2 = 0
+Bounds
+ 0 <= x <= 3
+ y free
+ 0 <= y_lambda_0 <= 1
+ 0 <= y_lambda_1 <= 1
+ 0 <= y_lambda_2 <= 1
+SOS
+ y_sos2: S2:: y_lambda_0:1 y_lambda_1:2 y_lambda_2:3
+End
+"
+        );
+
+        let mut mps = Vec::new();
+        model.write_mps(&mut mps).unwrap();
+        assert_eq!(
+            String::from_utf8(mps).unwrap(),
+            "NAME
+ROWS
+ N  obj
+ E  y_convexity
+ E  y_x
+ E  y_y
+COLUMNS
+    x  y_x  1
+    y  obj  -1
+    y  y_y  1
+    y_lambda_0  y_convexity  1
+    y_lambda_1  y_convexity  1
+    y_lambda_1  y_x  -1
+    y_lambda_1  y_y  -2
+    y_lambda_2  y_convexity  1
+    y_lambda_2  y_x  -3
+    y_lambda_2  y_y  -1
+RHS
+    RHS  y_convexity  1
+BOUNDS
+ UP BND  x  3
+ FR BND  y
+ UP BND  y_lambda_0  1
+ UP BND  y_lambda_1  1
+ UP BND  y_lambda_2  1
+SOS
+ S2 SOS  y_sos2  1
+    y_sos2  y_lambda_0  1
+    y_sos2  y_lambda_1  2
+    y_sos2  y_lambda_2  3
+ENDATA
+"
+        );
+    }
+}
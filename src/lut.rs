@@ -0,0 +1,359 @@
+// Copyright 2019 Matthieu Felix
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Lookup tables for fast evaluation of functions.
+
+use crate::{segment_slope, y_at_x, Coord, CoordFloat, Line, PiecewiseLinearFunction};
+
+/// The number of values evaluated at once by `UniformLut::eval_slice()`.
+const CHUNK: usize = 64;
+
+/// A function compiled for fast evaluation, returned by `compile_lut()` on
+/// `PiecewiseLinearFunction`.
+///
+/// The finite part of the domain is split into buckets of equal width, each of which stores the
+/// index of the first segment it may overlap. Finding the segment at `x` then takes one
+/// multiplication, one truncation and a scan over the points of inflection in the bucket, instead
+/// of a binary search over all of them.
+///
+/// `eval_slice()` first finds the segment of each value, then interpolates all of them with the
+/// precomputed slopes of the segments. The second pass has no branches, so that the compiler can
+/// vectorize it.
+///
+/// ## Example
+///
+/// ```
+/// use piecewise_linear::PiecewiseLinearFunction;
+/// use std::convert::TryFrom;
+/// let f = PiecewiseLinearFunction::try_from(vec![(0., 0.), (1., 1.), (2., 1.5)]).unwrap();
+/// let lut = f.compile_lut(16);
+/// assert_eq!(lut.y_at_x(1.25), f.y_at_x(1.25));
+/// let mut values = [0f64; 3];
+/// lut.eval_slice(&[0.5, 1.5, 3.], &mut values);
+/// assert_eq!(&values[..2], &[0.5, 1.25]);
+/// assert!(values[2].is_nan());
+/// ```
+#[derive(Clone, Debug)]
+pub struct UniformLut<T: CoordFloat> {
+    function: PiecewiseLinearFunction<T>,
+    /// The x value of the start of the first bucket.
+    origin: T,
+    /// The inverse of the width of the buckets.
+    scale: T,
+    /// The index of the first segment which may contain each bucket.
+    first_segments: Vec<usize>,
+    /// The segments of the function, followed by the constant entries `OUTSIDE`,
+    /// `AT_NEG_INFINITY` and `AT_INFINITY`.
+    segments: Vec<LutSegment<T>>,
+}
+
+/// Offsets of the constant entries after the segments in `UniformLut::segments`.
+const OUTSIDE: usize = 0;
+const AT_NEG_INFINITY: usize = 1;
+const AT_INFINITY: usize = 2;
+
+/// A segment prepared for interpolation, which gives the same results as `y_at_x()` at finite
+/// values.
+#[derive(Copy, Clone, Debug)]
+struct LutSegment<T: CoordFloat> {
+    /// The point from which `y_at_x()` interpolates.
+    anchor: Coord<T>,
+    slope: T,
+    /// The end of the segment, whose value is returned exactly.
+    end: Coord<T>,
+}
+
+impl<T: CoordFloat> LutSegment<T> {
+    fn new(line: &Line<T>) -> Self {
+        LutSegment {
+            anchor: if line.start.x.is_finite() {
+                line.start
+            } else {
+                line.end
+            },
+            slope: segment_slope(line),
+            end: line.end,
+        }
+    }
+
+    /// A segment whose value is `value` at 0.
+    fn constant(value: T) -> Self {
+        let point = Coord {
+            x: T::zero(),
+            y: value,
+        };
+        LutSegment {
+            anchor: point,
+            slope: T::zero(),
+            end: point,
+        }
+    }
+
+    #[inline]
+    fn interpolate(&self, x: T) -> T {
+        let interpolated = self.anchor.y + (x - self.anchor.x) * self.slope;
+        if x == self.end.x {
+            self.end.y
+        } else {
+            interpolated
+        }
+    }
+}
+
+// The segments contain NaN, so they are not compared; they are derived from the function anyway.
+impl<T: CoordFloat> PartialEq for UniformLut<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.function == other.function && self.first_segments == other.first_segments
+    }
+}
+
+impl<T: CoordFloat> PiecewiseLinearFunction<T> {
+    /// Compiles this function into a lookup table with `resolution` buckets (at least one) over
+    /// the finite part of its domain.
+    ///
+    /// The lookup table returns exactly the same values as `y_at_x()`. Evaluation is fastest when
+    /// there are about as many buckets as points of inflection, or more if they are unevenly
+    /// spaced.
+    pub fn compile_lut(&self, resolution: usize) -> UniformLut<T> {
+        let resolution = resolution.max(1);
+        let finite: Vec<T> = self
+            .coordinates
+            .iter()
+            .map(|c| c.x)
+            .filter(|x| x.is_finite())
+            .collect();
+        let origin = finite[0];
+        // This unwrap is guaranteed to succeed as functions have at least one finite point.
+        let width = *finite.last().unwrap() - origin;
+        let scale = if width > T::zero() {
+            T::from(resolution).unwrap() / width
+        } else {
+            T::zero()
+        };
+        let lines: Vec<_> = self.segments_iter().collect();
+        let mut segments: Vec<_> = lines.iter().map(LutSegment::new).collect();
+        // The values at infinity are only used if the domain is infinite on that side.
+        segments.extend([
+            LutSegment::constant(T::nan()),
+            LutSegment::constant(y_at_x(&lines[0], T::neg_infinity())),
+            LutSegment::constant(y_at_x(&lines[lines.len() - 1], T::infinity())),
+        ]);
+        // The table is filled below, but `bucket()` already needs its length.
+        let mut lut = UniformLut {
+            function: self.clone(),
+            origin,
+            scale,
+            first_segments: vec![0; resolution],
+            segments,
+        };
+
+        // `bucket()` is non-decreasing, so the points in earlier buckets are all before any `x`
+        // in a given bucket. The last of them starts the first segment which may contain `x`.
+        let mut points_before = 0;
+        let first_segments = (0..resolution)
+            .map(|bucket| {
+                while points_before < self.coordinates.len()
+                    && lut.bucket(self.coordinates[points_before].x) < bucket
+                {
+                    points_before += 1;
+                }
+                points_before.saturating_sub(1)
+            })
+            .collect();
+        lut.first_segments = first_segments;
+        lut
+    }
+}
+
+impl<T: CoordFloat> UniformLut<T> {
+    /// Returns the compiled function.
+    pub fn function(&self) -> &PiecewiseLinearFunction<T> {
+        &self.function
+    }
+
+    /// Returns the number of buckets of the lookup table.
+    pub fn resolution(&self) -> usize {
+        self.first_segments.len()
+    }
+
+    /// Computes the value f(x), with the same result as `y_at_x()` on the compiled function.
+    ///
+    /// Returns `None` if `x` is outside the domain of f.
+    #[inline]
+    pub fn y_at_x(&self, x: T) -> Option<T> {
+        let coordinates = &self.function.coordinates;
+        let last = coordinates.len() - 1;
+        if x.is_nan() {
+            return self.function.y_at_x(x);
+        } else if x < coordinates[0].x || x > coordinates[last].x {
+            return None;
+        }
+        let segment = self.segment(x);
+        Some(y_at_x(
+            &Line::new(coordinates[segment], coordinates[segment + 1]),
+            x,
+        ))
+    }
+
+    /// Evaluates the function at each value of `xs`, writing the results to `ys`. Values outside
+    /// the domain of the function are NaN.
+    ///
+    /// Panics if `xs` and `ys` have different lengths.
+    pub fn eval_slice(&self, xs: &[T], ys: &mut [T]) {
+        assert_eq!(xs.len(), ys.len());
+        let coordinates = &self.function.coordinates;
+        let (start, end) = (coordinates[0].x, coordinates[coordinates.len() - 1].x);
+        let constants = coordinates.len() - 1;
+        let mut entries = [0; CHUNK];
+        for (xs, ys) in xs.chunks(CHUNK).zip(ys.chunks_mut(CHUNK)) {
+            // Find the entry of `segments` giving the value at each `x`. Constants are evaluated
+            // at 0, which is written to `ys` in place of `x`.
+            for ((&x, y), entry) in xs.iter().zip(ys.iter_mut()).zip(entries.iter_mut()) {
+                *entry = if x.is_nan() || x < start || x > end {
+                    constants + OUTSIDE
+                } else if x == T::neg_infinity() {
+                    constants + AT_NEG_INFINITY
+                } else if x == T::infinity() {
+                    constants + AT_INFINITY
+                } else {
+                    self.segment(x)
+                };
+                *y = if *entry < constants { x } else { T::zero() };
+            }
+            for (y, &entry) in ys.iter_mut().zip(&entries) {
+                *y = self.segments[entry].interpolate(*y);
+            }
+        }
+    }
+
+    /// Returns the index of the segment which `segment_at_x()` would return for a finite `x` in
+    /// the domain: the one ending at the first point whose x value is at least `x`, or the first
+    /// one.
+    #[inline]
+    fn segment(&self, x: T) -> usize {
+        let coordinates = &self.function.coordinates;
+        let last = coordinates.len() - 1;
+        let mut segment = self.first_segments[self.bucket(x)];
+        while segment + 1 < last && coordinates[segment + 1].x < x {
+            segment += 1;
+        }
+        segment
+    }
+
+    /// Returns the bucket containing `x`, where values before or after the buckets are in the
+    /// first or last one.
+    #[inline]
+    fn bucket(&self, x: T) -> usize {
+        let position = (x - self.origin) * self.scale;
+        if position < T::one() {
+            0
+        } else {
+            position
+                .to_usize()
+                .map_or(self.first_segments.len() - 1, |b| {
+                    b.min(self.first_segments.len() - 1)
+                })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use super::*;
+
+    /// Checks that `lut` gives the same results as `y_at_x()` on many points, including all the
+    /// points of inflection, one at a time and in batches.
+    fn check_exact(f: &PiecewiseLinearFunction<f64>, lut: &UniformLut<f64>) {
+        let mut xs: Vec<f64> = f.coordinates.iter().map(|c| c.x).collect();
+        for i in 0..=1000 {
+            xs.push(-1. + 0.0123 * f64::from(i));
+        }
+        xs.extend_from_slice(&[f64::NEG_INFINITY, f64::INFINITY, -0., 1e300, -1e300]);
+        for &x in &xs {
+            let (expected, actual) = (f.y_at_x(x), lut.y_at_x(x));
+            assert_eq!(
+                expected.map(f64::to_bits),
+                actual.map(f64::to_bits),
+                "at {}",
+                x
+            );
+        }
+        let mut ys = vec![0.; xs.len()];
+        lut.eval_slice(&xs, &mut ys);
+        for (&x, &y) in xs.iter().zip(&ys) {
+            match f.y_at_x(x) {
+                Some(expected) => assert_eq!(expected.to_bits(), y.to_bits(), "at {}", x),
+                None => assert!(y.is_nan(), "at {}", x),
+            }
+        }
+    }
+
+    fn get_test_function() -> PiecewiseLinearFunction<f64> {
+        // Unevenly spaced points, with values which do not round nicely.
+        PiecewiseLinearFunction::try_from(vec![
+            (0., 0.1),
+            (0.3, 0.7),
+            (0.31, -0.2),
+            (0.35, 1. / 3.),
+            (1.7, 2.9),
+            (5.1, 0.3),
+            (5.15, 0.35),
+            (9.99, -1.1),
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn test_exact() {
+        let f = get_test_function();
+        for &resolution in &[0, 1, 2, 7, 8, 100, 10_000] {
+            let lut = f.compile_lut(resolution);
+            assert_eq!(lut.resolution(), resolution.max(1));
+            check_exact(&f, &lut);
+        }
+        assert_eq!(f.compile_lut(8).function(), &f);
+    }
+
+    #[test]
+    fn test_infinite_domains() {
+        let f = PiecewiseLinearFunction::with_edge_slopes(
+            vec![(0.5, 1.).into(), (2., 3.).into(), (2.5, 2.).into()],
+            Some(-2.),
+            Some(0.),
+        )
+        .unwrap();
+        check_exact(&f, &f.compile_lut(4));
+        let single =
+            PiecewiseLinearFunction::with_edge_slopes(vec![(1., 1.).into()], Some(1.), Some(2.))
+                .unwrap();
+        check_exact(&single, &single.compile_lut(4));
+    }
+
+    #[test]
+    fn test_eval_slice() {
+        let f = get_test_function();
+        let lut = f.compile_lut(8);
+        let xs = [-1., 0., 0.305, 3., 9.99, 10., f64::NAN];
+        let mut ys = [0.; 7];
+        lut.eval_slice(&xs, &mut ys);
+        for (x, y) in xs.iter().zip(&ys) {
+            match f.y_at_x(*x) {
+                Some(expected) if !expected.is_nan() => assert_eq!(*y, expected),
+                _ => assert!(y.is_nan()),
+            }
+        }
+    }
+}
@@ -0,0 +1,208 @@
+// Copyright 2019 Matthieu Felix
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Averages and statistical moments of functions.
+
+use crate::{CoordFloat, Line, PiecewiseLinearFunction};
+
+impl<T: CoordFloat> PiecewiseLinearFunction<T> {
+    /// Returns the mean value of this function over its domain, that is, its integral divided by
+    /// the width of its domain.
+    ///
+    /// Returns `None` if the domain is not bounded.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use piecewise_linear::PiecewiseLinearFunction;
+    /// use std::convert::TryFrom;
+    /// let power = PiecewiseLinearFunction::try_from(vec![(0., 0.), (1., 2.), (4., 2.)]).unwrap();
+    /// assert_eq!(power.mean_value(), Some(7. / 4.));
+    /// assert_eq!(power.average_over(0., 2.), Some(1.5));
+    /// ```
+    pub fn mean_value(&self) -> Option<T> {
+        if !self.is_bounded() {
+            return None;
+        }
+        let (start, end) = self.domain();
+        Some(self.moment_integral(T::zero(), T::zero(), 0) / (end - start))
+    }
+
+    /// Returns the mean value of this function over [`from`, `to`].
+    ///
+    /// Returns the value at `from` if `from == to`, and `None` if `from > to` or if [`from`, `to`]
+    /// is not included in the domain of the function.
+    pub fn average_over(&self, from: T, to: T) -> Option<T> {
+        if from == to {
+            self.y_at_x(from)
+        } else if from < to {
+            self.shrink_domain((from, to))?.mean_value()
+        } else {
+            None
+        }
+    }
+
+    /// Returns the centroid of the area under this function along the _x_ axis, that is, the
+    /// integral of x f(x) divided by the integral of f. This is the mean if f is a density.
+    ///
+    /// Returns `None` if the domain is not bounded, or if the integral of f is zero.
+    pub fn weighted_mean_x(&self) -> Option<T> {
+        let total = self.total_weight()?;
+        let reference = self.domain().0;
+        Some(reference + self.moment_integral(reference, T::zero(), 1) / total)
+    }
+
+    /// Returns the variance of the distribution whose density is proportional to this function.
+    ///
+    /// Returns `None` if the domain is not bounded, or if the integral of f is zero.
+    pub fn variance(&self) -> Option<T> {
+        self.central_moment(2)
+    }
+
+    /// Returns the `k`-th raw moment of the distribution whose density is proportional to this
+    /// function, that is, the integral of x^k f(x) divided by the integral of f.
+    ///
+    /// Returns `None` if `k` is negative, if the domain is not bounded, or if the integral of f is
+    /// zero.
+    pub fn raw_moment(&self, k: i32) -> Option<T> {
+        if k < 0 {
+            return None;
+        }
+        let total = self.total_weight()?;
+        Some(self.moment_integral(T::zero(), T::zero(), k) / total)
+    }
+
+    /// Returns the `k`-th central moment of the distribution whose density is proportional to this
+    /// function, that is, the integral of (x - m)^k f(x) divided by the integral of f, where m is
+    /// `weighted_mean_x()`.
+    ///
+    /// Returns `None` if `k` is negative, if the domain is not bounded, or if the integral of f is
+    /// zero.
+    pub fn central_moment(&self, k: i32) -> Option<T> {
+        if k < 0 {
+            return None;
+        }
+        let total = self.total_weight()?;
+        // The mean is computed relative to a point of the domain, so that it stays accurate far
+        // from zero.
+        let reference = self.domain().0;
+        let offset = self.moment_integral(reference, T::zero(), 1) / total;
+        Some(self.moment_integral(reference, offset, k) / total)
+    }
+
+    /// Returns the integral of f, or `None` if the domain is not bounded or the integral is zero.
+    fn total_weight(&self) -> Option<T> {
+        if !self.is_bounded() {
+            return None;
+        }
+        let total = self.moment_integral(T::zero(), T::zero(), 0);
+        if total == T::zero() {
+            None
+        } else {
+            Some(total)
+        }
+    }
+
+    /// Returns the integral of (x - c)^k f(x) over the domain, which must be bounded, where
+    /// c = `reference` + `offset`, and `k` is non-negative.
+    fn moment_integral(&self, reference: T, offset: T, k: i32) -> T {
+        self.segments_iter()
+            .map(|s| segment_moment_integral(&s, (s.start.x - reference) - offset, k))
+            .fold(T::zero(), |a, b| a + b)
+    }
+}
+
+/// Returns the integral of (x - c)^k l(x) over the finite segment `line`, where `start` is
+/// `line.start.x - c` and `k` is non-negative.
+///
+/// With x = `line.start.x` + w t and t in [0, 1], the integral is the sum over j of
+/// C(k, j) start^(k - j) w^(j + 1) ∫ t^j l dt. All the terms are computed relative to the start
+/// of the segment, which avoids cancellations far from zero.
+fn segment_moment_integral<T: CoordFloat>(line: &Line<T>, start: T, k: i32) -> T {
+    let width = line.end.x - line.start.x;
+    let (y0, dy) = (line.start.y, line.end.y - line.start.y);
+    let mut total = T::zero();
+    // C(k, j) start^(k - j) w^j, updated for each j.
+    let mut binomial = T::one();
+    for j in 0..=k {
+        let term = binomial * start.powi(k - j);
+        let (j1, j2) = (T::from(j + 1).unwrap(), T::from(j + 2).unwrap());
+        total = total + term * (y0 / j1 + dy / j2);
+        binomial = binomial * width * T::from(k - j).unwrap() / j1;
+    }
+    total * width
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use super::*;
+
+    #[test]
+    fn test_averages() {
+        let f = PiecewiseLinearFunction::try_from(vec![(0., 0.), (1., 2.), (4., 2.)]).unwrap();
+        assert_eq!(f.mean_value(), Some(1.75));
+        assert_eq!(f.average_over(1., 4.), Some(2.));
+        assert_eq!(f.average_over(0.5, 0.5), Some(1.));
+        assert_eq!(f.average_over(2., 1.), None);
+        assert_eq!(f.average_over(-1., 1.), None);
+        let unbounded = PiecewiseLinearFunction::constant((0., f64::INFINITY), 1.).unwrap();
+        assert_eq!(unbounded.mean_value(), None);
+        assert_eq!(unbounded.average_over(0., 10.), Some(1.));
+    }
+
+    #[test]
+    fn test_moments() {
+        // The uniform distribution over [0, 3].
+        let uniform = PiecewiseLinearFunction::constant((0., 3.), 5.).unwrap();
+        assert_eq!(uniform.weighted_mean_x(), Some(1.5));
+        assert_eq!(uniform.variance(), Some(0.75));
+        assert_eq!(uniform.central_moment(3), Some(0.));
+        assert_eq!(uniform.raw_moment(2), Some(3.));
+
+        // The triangular distribution over [0, 3] with mode 1.
+        let triangular =
+            PiecewiseLinearFunction::try_from(vec![(0f64, 0.), (1., 2.), (3., 0.)]).unwrap();
+        let mean = triangular.weighted_mean_x().unwrap();
+        assert!((mean - 4. / 3.).abs() < 1e-12);
+        assert!((triangular.variance().unwrap() - (1. + 9. - 3.) / 18.).abs() < 1e-12);
+        let skewness =
+            triangular.central_moment(3).unwrap() / triangular.variance().unwrap().powf(1.5);
+        // With a = 0, b = 3 and c = 1, this is √2 (a + b - 2c) (2a - b - c) (a - 2b + c) / 5 / 7^1.5.
+        let expected = 2f64.sqrt() * 1. * -4. * -5. / 5. / 7f64.powf(1.5);
+        assert!((skewness - expected).abs() < 1e-12);
+
+        let zero = PiecewiseLinearFunction::try_from(vec![(0., 1.), (2., -1.)]).unwrap();
+        assert_eq!(zero.weighted_mean_x(), None);
+        assert_eq!(uniform.raw_moment(-1), None);
+        assert_eq!(uniform.central_moment(-2), None);
+    }
+
+    #[test]
+    fn test_moments_far_from_zero() {
+        // The triangular distribution over [s, s + 20] with mode s + 10.
+        let start = 1.7e9f64;
+        let triangular = PiecewiseLinearFunction::try_from(vec![
+            (start, 0.),
+            (start + 10., 0.1),
+            (start + 20., 0.),
+        ])
+        .unwrap();
+        assert!((triangular.weighted_mean_x().unwrap() - (start + 10.)).abs() < 1e-6);
+        assert!((triangular.variance().unwrap() - 300. / 18.).abs() < 1e-6);
+        assert!(triangular.central_moment(3).unwrap().abs() < 1e-6);
+        assert!((triangular.mean_value().unwrap() - 0.05).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,401 @@
+// Copyright 2019 Matthieu Felix
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Utilities for [network calculus](https://en.wikipedia.org/wiki/Network_calculus).
+//!
+//! Arrival and service curves are represented as piecewise linear functions, usually over
+//! [0, inf[. Delay and backlog bounds are computed exactly from their points of inflection.
+//!
+//! ## Example
+//!
+//! ```
+//! use piecewise_linear::network_calculus::*;
+//! let alpha = token_bucket(1., 2.).unwrap();
+//! let beta = rate_latency(3., 2.).unwrap();
+//! assert_eq!(vertical_deviation(&alpha, &beta), Some(4.));
+//! assert_eq!(horizontal_deviation(&alpha, &beta), Some(2. + 2. / 3.));
+//! ```
+
+use num_traits::Signed;
+
+use crate::{segment_slope, Coord, CoordFloat, Line, PiecewiseLinearFunction};
+
+/// Maximum number of doublings performed by `subadditive_closure()`.
+const MAX_CLOSURE_ITERATIONS: usize = 64;
+
+/// Returns the token bucket arrival curve t -> `burst` + `rate` * t, over [0, inf[.
+///
+/// Token bucket curves are usually defined to be 0 at t = 0; since discontinuous functions are not
+/// supported, the returned curve is `burst` at 0.
+///
+/// Returns `None` if either parameter is negative or not finite.
+pub fn token_bucket<T: CoordFloat>(rate: T, burst: T) -> Option<PiecewiseLinearFunction<T>> {
+    if !is_non_negative(rate) || !is_non_negative(burst) {
+        return None;
+    }
+    PiecewiseLinearFunction::with_edge_slopes(
+        vec![Coord {
+            x: T::zero(),
+            y: burst,
+        }],
+        None,
+        Some(rate),
+    )
+}
+
+/// Returns the rate-latency service curve t -> `rate` * max(0, t - `latency`), over [0, inf[.
+///
+/// Returns `None` if either parameter is negative or not finite.
+pub fn rate_latency<T: CoordFloat>(rate: T, latency: T) -> Option<PiecewiseLinearFunction<T>> {
+    if !is_non_negative(rate) || !is_non_negative(latency) {
+        return None;
+    }
+    let mut coordinates = vec![Coord {
+        x: T::zero(),
+        y: T::zero(),
+    }];
+    if latency > T::zero() {
+        coordinates.push(Coord {
+            x: latency,
+            y: T::zero(),
+        });
+    }
+    PiecewiseLinearFunction::with_edge_slopes(coordinates, None, Some(rate))
+}
+
+/// Computes the vertical deviation sup { alpha(t) - beta(t) } between two functions, over the
+/// intersection of their domains.
+///
+/// When `alpha` is an arrival curve and `beta` a service curve, this is a bound on the backlog.
+/// Returns `None` if the domains of `alpha` and `beta` do not overlap, and +inf if the deviation is
+/// not bounded.
+pub fn vertical_deviation<T: CoordFloat + Signed>(
+    alpha: &PiecewiseLinearFunction<T>,
+    beta: &PiecewiseLinearFunction<T>,
+) -> Option<T> {
+    let (alpha_domain, beta_domain) = (alpha.domain(), beta.domain());
+    let domain = (
+        alpha_domain.0.max(beta_domain.0),
+        alpha_domain.1.min(beta_domain.1),
+    );
+    if domain.0 >= domain.1 {
+        return None;
+    }
+    let difference = alpha
+        .shrink_domain(domain)?
+        .add(&beta.shrink_domain(domain)?.negate())?;
+    Some(supremum(&difference))
+}
+
+/// Computes the horizontal deviation between two functions, that is, the supremum over all `t` in
+/// the domain of `alpha` of inf { d >= 0 : alpha(t) <= beta(t + d) }.
+///
+/// When `alpha` is an arrival curve and `beta` a service curve, this is a bound on the delay.
+/// Returns +inf if the deviation is not bounded. Returns `None` if `beta` is not non-decreasing,
+/// or if its domain is bounded and it never reaches some value of `alpha`.
+pub fn horizontal_deviation<T: CoordFloat>(
+    alpha: &PiecewiseLinearFunction<T>,
+    beta: &PiecewiseLinearFunction<T>,
+) -> Option<T> {
+    if !beta.segments_iter().all(|s| segment_slope(&s) >= T::zero()) {
+        return None;
+    }
+    let (alpha_tail, beta_tail) = (alpha.edge_slopes().1, beta.edge_slopes().1);
+    if !alpha.domain().1.is_finite() && alpha_tail > T::zero() && alpha_tail > beta_tail {
+        return Some(T::infinity());
+    }
+
+    let mut deviation = T::zero();
+    for segment in alpha.segments_iter() {
+        for t in candidate_points(&segment, beta) {
+            let value = crate::y_at_x(&segment, t);
+            // If alpha is above its value at t somewhere around t, the deviation around t gets
+            // arbitrarily close to the upper pseudo-inverse of beta.
+            let slope = segment_slope(&segment);
+            let rises = if t == segment.start.x {
+                slope > T::zero()
+            } else if t == segment.end.x {
+                slope < T::zero()
+            } else {
+                true
+            };
+            let mut inverse = match lower_pseudo_inverse(beta, value) {
+                Some(inverse) => inverse,
+                // An unbounded beta which plateaus below `value` never catches up with alpha.
+                None if !beta.domain().1.is_finite() => return Some(T::infinity()),
+                None => return None,
+            };
+            if rises {
+                let coordinates = &beta.coordinates;
+                let last = coordinates[coordinates.len() - 1];
+                if last.x.is_finite() && value >= last.y {
+                    // Beta never reaches the values of alpha just above `value`.
+                    return None;
+                }
+                if !last.x.is_finite()
+                    && last.y == T::zero()
+                    && value >= coordinates[coordinates.len() - 2].y
+                {
+                    // Beta plateaus at `value`, so it never catches up with alpha.
+                    return Some(T::infinity());
+                }
+                inverse = upper_pseudo_inverse(beta, value);
+            }
+            deviation = deviation.max(inverse - t);
+        }
+    }
+    Some(deviation)
+}
+
+/// Computes the sub-additive closure of `alpha` over [0, `horizon`], that is, the infimum over
+/// all n >= 1 of the n-fold min-plus self-convolution of `alpha`.
+///
+/// The closure is usually also defined to be 0 at t = 0; since discontinuous functions are not
+/// supported, this is not reflected in the returned function.
+///
+/// Returns `None` if the domain of `alpha` does not contain [0, `horizon`], or if the closure does
+/// not converge after a large number of iterations.
+pub fn subadditive_closure<T: CoordFloat + Signed>(
+    alpha: &PiecewiseLinearFunction<T>,
+    horizon: T,
+) -> Option<PiecewiseLinearFunction<T>> {
+    let domain = (T::zero(), horizon);
+    let mut closure = alpha.shrink_domain(domain)?;
+    // Each iteration covers twice as many self-convolutions as the previous one.
+    for _ in 0..MAX_CLOSURE_ITERATIONS {
+        let doubled = closure
+            .min_plus_convolution(&closure)?
+            .shrink_domain(domain)?;
+        let next = closure.min(&doubled)?;
+        let converged = closure
+            .points_of_inflection_iter(&next)?
+            .all(|(_, values)| {
+                let tolerance = T::epsilon() * T::from(64).unwrap() * values[0].abs().max(T::one());
+                (values[0] - values[1]).abs() <= tolerance
+            });
+        closure = next;
+        if converged {
+            return Some(closure);
+        }
+    }
+    None
+}
+
+fn is_non_negative<T: CoordFloat>(value: T) -> bool {
+    value.is_finite() && value >= T::zero()
+}
+
+/// Returns the supremum of a function over its domain, taking limits at infinity into account.
+fn supremum<T: CoordFloat>(f: &PiecewiseLinearFunction<T>) -> T {
+    let (left_slope, right_slope) = f.edge_slopes();
+    let (start, end) = f.domain();
+    if (start.is_infinite() && left_slope < T::zero())
+        || (end.is_infinite() && right_slope > T::zero())
+    {
+        return T::infinity();
+    }
+    f.coordinates
+        .iter()
+        .filter(|c| c.x.is_finite())
+        .map(|c| c.y)
+        .fold(T::neg_infinity(), T::max)
+}
+
+/// Returns the points of `segment` (of alpha) where the horizontal deviation may be maximal: its
+/// finite endpoints, and the points where it reaches the value of a point of inflection of beta.
+fn candidate_points<T: CoordFloat>(segment: &Line<T>, beta: &PiecewiseLinearFunction<T>) -> Vec<T> {
+    let mut points: Vec<T> = vec![segment.start.x, segment.end.x]
+        .into_iter()
+        .filter(|x| x.is_finite())
+        .collect();
+    let slope = segment_slope(segment);
+    if slope != T::zero() {
+        let anchor = crate::finite_endpoint(segment);
+        for level in beta.coordinates.iter().filter(|c| c.x.is_finite()) {
+            let t = anchor.x + (level.y - anchor.y) / slope;
+            if t > segment.start.x && t < segment.end.x {
+                points.push(t);
+            }
+        }
+    }
+    points
+}
+
+/// Returns inf { s : beta(s) >= y } for a non-decreasing `beta`, or `None` if beta never reaches
+/// `y` over its domain.
+fn lower_pseudo_inverse<T: CoordFloat>(beta: &PiecewiseLinearFunction<T>, y: T) -> Option<T> {
+    if beta.coordinates[0].x.is_finite() && beta.coordinates[0].y >= y {
+        return Some(beta.coordinates[0].x);
+    }
+    beta.segments_iter().find_map(|segment| {
+        let end_value = crate::y_at_x(&segment, segment.end.x);
+        if end_value < y {
+            None
+        } else {
+            Some(inverse_on_segment(&segment, y))
+        }
+    })
+}
+
+/// Returns sup { s : beta(s) <= y } for a non-decreasing `beta`, or the start of its domain if
+/// there is no such `s`.
+fn upper_pseudo_inverse<T: CoordFloat>(beta: &PiecewiseLinearFunction<T>, y: T) -> T {
+    let segments: Vec<_> = beta.segments_iter().collect();
+    segments
+        .iter()
+        .rev()
+        .find_map(|segment| {
+            let start_value = crate::y_at_x(segment, segment.start.x);
+            if start_value > y {
+                None
+            } else if crate::y_at_x(segment, segment.end.x) <= y {
+                Some(segment.end.x)
+            } else {
+                Some(inverse_on_segment(segment, y))
+            }
+        })
+        .unwrap_or(beta.coordinates[0].x)
+}
+
+/// Returns the `x` at which the increasing `segment` has value `y`.
+fn inverse_on_segment<T: CoordFloat>(segment: &Line<T>, y: T) -> T {
+    let anchor = crate::finite_endpoint(segment);
+    anchor.x + (y - anchor.y) / segment_slope(segment)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use super::*;
+
+    #[test]
+    fn test_curves() {
+        assert_eq!(
+            token_bucket(2., 3.).unwrap(),
+            PiecewiseLinearFunction::try_from(vec![(0., 3.), (f64::INFINITY, 2.)]).unwrap()
+        );
+        assert_eq!(
+            rate_latency(2., 3.).unwrap(),
+            PiecewiseLinearFunction::try_from(vec![(0., 0.), (3., 0.), (f64::INFINITY, 2.)])
+                .unwrap()
+        );
+        assert_eq!(rate_latency(-1., 3.), None);
+        assert_eq!(token_bucket(1., f64::NAN), None);
+    }
+
+    #[test]
+    fn test_token_bucket_rate_latency_bounds() {
+        let alpha = token_bucket(1., 2.).unwrap();
+        let beta = rate_latency(3., 2.).unwrap();
+        assert_eq!(vertical_deviation(&alpha, &beta), Some(4.));
+        assert_eq!(horizontal_deviation(&alpha, &beta), Some(2. + 2. / 3.));
+
+        let fast_alpha = token_bucket(4., 2.).unwrap();
+        assert_eq!(vertical_deviation(&fast_alpha, &beta), Some(f64::INFINITY));
+        assert_eq!(
+            horizontal_deviation(&fast_alpha, &beta),
+            Some(f64::INFINITY)
+        );
+    }
+
+    #[test]
+    fn test_concave_arrival_bounds() {
+        let alpha = token_bucket(5., 1.)
+            .unwrap()
+            .min(&token_bucket(1., 4.).unwrap())
+            .unwrap();
+        let beta = rate_latency(2., 1.).unwrap();
+        assert_eq!(vertical_deviation(&alpha, &beta), Some(5.));
+        assert_eq!(horizontal_deviation(&alpha, &beta), Some(2.625));
+    }
+
+    #[test]
+    fn test_vertical_deviation_disjoint_domains() {
+        let alpha = PiecewiseLinearFunction::try_from(vec![(0., 0.), (1., 1.)]).unwrap();
+        let beta = PiecewiseLinearFunction::try_from(vec![(2., 0.), (3., 1.)]).unwrap();
+        assert_eq!(vertical_deviation(&alpha, &beta), None);
+        assert_eq!(vertical_deviation(&beta, &alpha), None);
+        // Domains which only touch at one point do not overlap either.
+        let touching = PiecewiseLinearFunction::try_from(vec![(1., 0.), (2., 1.)]).unwrap();
+        assert_eq!(vertical_deviation(&alpha, &touching), None);
+    }
+
+    #[test]
+    fn test_horizontal_deviation_flat_service() {
+        // The service stalls between t = 2 and t = 4 at level 2.
+        let beta = PiecewiseLinearFunction::try_from(vec![(0., 0.), (2., 2.), (4., 2.), (6., 6.)])
+            .unwrap();
+        let alpha = PiecewiseLinearFunction::try_from(vec![(0., 1.), (1., 2.5)]).unwrap();
+        // Just after alpha reaches 2 at t = 2/3, beta only gets there at t = 4.
+        assert_eq!(horizontal_deviation(&alpha, &beta), Some(4. - 2. / 3.));
+
+        let too_much = PiecewiseLinearFunction::try_from(vec![(0., 1.), (1., 7.)]).unwrap();
+        assert_eq!(horizontal_deviation(&too_much, &beta), None);
+        assert_eq!(horizontal_deviation(&alpha, &beta.negate()), None);
+    }
+
+    #[test]
+    fn test_horizontal_deviation_plateau() {
+        // The service stops for good once it has served 2 units.
+        let beta = PiecewiseLinearFunction::try_from(vec![(0., 0.), (2., 2.), (f64::INFINITY, 0.)])
+            .unwrap();
+        let alpha = PiecewiseLinearFunction::try_from(vec![(0., 0.), (1., 2.)]).unwrap();
+        assert_eq!(horizontal_deviation(&alpha, &beta), Some(1.));
+        let above = PiecewiseLinearFunction::try_from(vec![(0., 1.), (1., 3.)]).unwrap();
+        assert_eq!(horizontal_deviation(&above, &beta), Some(f64::INFINITY));
+        let reaching =
+            PiecewiseLinearFunction::try_from(vec![(0., 0.), (1., 2.), (2., 2.5)]).unwrap();
+        assert_eq!(horizontal_deviation(&reaching, &beta), Some(f64::INFINITY));
+    }
+
+    #[test]
+    fn test_subadditive_closure() {
+        let alpha = token_bucket(1., 2.).unwrap();
+        assert_eq!(
+            subadditive_closure(&alpha, 10.).unwrap(),
+            alpha.shrink_domain((0., 10.)).unwrap()
+        );
+
+        let beta = rate_latency(2., 1.).unwrap();
+        let closure = subadditive_closure(&beta, 8.).unwrap();
+        for i in 0..=80 {
+            assert_eq!(closure.y_at_x(f64::from(i) / 10.), Some(0.));
+        }
+
+        let staircase = PiecewiseLinearFunction::try_from(vec![
+            (0., 1.),
+            (1., 1.),
+            (1.25, 3.),
+            (2., 3.),
+            (2.25, 5.),
+            (6., 5.),
+        ])
+        .unwrap();
+        let closure = subadditive_closure(&staircase, 6.).unwrap();
+        for i in 1..=60 {
+            let t = f64::from(i) / 10.;
+            assert!(closure.y_at_x(t).unwrap() <= staircase.y_at_x(t).unwrap() + 1e-12);
+            for j in 1..i {
+                let s = f64::from(j) / 10.;
+                assert!(
+                    closure.y_at_x(t).unwrap()
+                        <= closure.y_at_x(s).unwrap() + closure.y_at_x(t - s).unwrap() + 1e-12
+                );
+            }
+        }
+        assert_eq!(closure.y_at_x(4.), Some(4.));
+        assert_eq!(subadditive_closure(&staircase, 7.), None);
+    }
+}
@@ -0,0 +1,196 @@
+// Copyright 2019 Matthieu Felix
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! L1, L2 and L∞ norms of functions, and distances between functions.
+
+use crate::{finite_endpoint, segment_slope, Coord, CoordFloat, PiecewiseLinearFunction};
+
+impl<T: CoordFloat> PiecewiseLinearFunction<T> {
+    /// Returns the L1 norm of this function, that is, the integral of |f|.
+    ///
+    /// Segments are split where f changes sign, so the result is exact. Returns +inf if the domain
+    /// is infinite and f is not zero on the edge pieces.
+    pub fn norm_l1(&self) -> T {
+        let two = T::one() + T::one();
+        self.segments_iter()
+            .map(|s| {
+                if s.start.x.is_infinite() || s.end.x.is_infinite() {
+                    return edge_norm(finite_endpoint(&s).y, segment_slope(&s));
+                }
+                let (y0, y1) = (s.start.y, s.end.y);
+                let width = s.end.x - s.start.x;
+                if (y0 >= T::zero()) == (y1 >= T::zero()) || y0 == T::zero() || y1 == T::zero() {
+                    width * (y0.abs() + y1.abs()) / two
+                } else {
+                    // The function crosses zero: add the areas of both triangles.
+                    width * (y0 * y0 + y1 * y1) / (two * (y0.abs() + y1.abs()))
+                }
+            })
+            .fold(T::zero(), |a, b| a + b)
+    }
+
+    /// Returns the L2 norm of this function, that is, the square root of the integral of f².
+    ///
+    /// Returns +inf if the domain is infinite and f is not zero on the edge pieces.
+    pub fn norm_l2(&self) -> T {
+        let three = T::one() + T::one() + T::one();
+        self.segments_iter()
+            .map(|s| {
+                if s.start.x.is_infinite() || s.end.x.is_infinite() {
+                    return edge_norm(finite_endpoint(&s).y, segment_slope(&s));
+                }
+                let (y0, y1) = (s.start.y, s.end.y);
+                (s.end.x - s.start.x) * (y0 * y0 + y0 * y1 + y1 * y1) / three
+            })
+            .fold(T::zero(), |a, b| a + b)
+            .sqrt()
+    }
+
+    /// Returns the L∞ norm of this function, that is, the greatest value of |f|.
+    ///
+    /// Returns +inf if the domain is infinite and f is not constant on the edge pieces.
+    pub fn norm_linf(&self) -> T {
+        self.coordinates
+            .iter()
+            .map(|c| {
+                if c.x.is_finite() {
+                    c.y.abs()
+                } else if c.y == T::zero() {
+                    T::zero()
+                } else {
+                    T::infinity()
+                }
+            })
+            .fold(T::zero(), T::max)
+    }
+
+    /// Returns the L1 distance between this function and `other`, that is, the integral of
+    /// |f - g|.
+    ///
+    /// Returns `None` if the domains of `self` and `other` are not equal.
+    pub fn distance_l1(&self, other: &PiecewiseLinearFunction<T>) -> Option<T> {
+        self.difference(other).map(|d| d.norm_l1())
+    }
+
+    /// Returns the L2 distance between this function and `other`, that is, the square root of the
+    /// integral of (f - g)².
+    ///
+    /// Returns `None` if the domains of `self` and `other` are not equal.
+    pub fn distance_l2(&self, other: &PiecewiseLinearFunction<T>) -> Option<T> {
+        self.difference(other).map(|d| d.norm_l2())
+    }
+
+    /// Returns the L∞ distance between this function and `other`, that is, the greatest value of
+    /// |f - g|.
+    ///
+    /// Returns `None` if the domains of `self` and `other` are not equal.
+    pub fn distance_linf(&self, other: &PiecewiseLinearFunction<T>) -> Option<T> {
+        self.difference(other).map(|d| d.norm_linf())
+    }
+
+    /// Returns f - g, with points at the points of inflection of both functions.
+    fn difference(&self, other: &PiecewiseLinearFunction<T>) -> Option<PiecewiseLinearFunction<T>> {
+        let coordinates = self
+            .points_of_inflection_iter(other)?
+            .map(|(x, values)| Coord {
+                x,
+                y: values[0] - values[1],
+            })
+            .collect();
+        // This unwrap is guaranteed to succeed as the points of inflection have increasing x
+        // values.
+        Some(PiecewiseLinearFunction::new(coordinates).unwrap())
+    }
+}
+
+/// Returns the contribution to the L1 norm, or to the square of the L2 norm, of an edge piece
+/// with value `y` at its finite end and slope `slope`.
+fn edge_norm<T: CoordFloat>(y: T, slope: T) -> T {
+    if y == T::zero() && slope == T::zero() {
+        T::zero()
+    } else {
+        T::infinity()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use super::*;
+
+    /// Computes the integral of |f|^p numerically.
+    fn numerical_integral(f: &PiecewiseLinearFunction<f64>, p: i32) -> f64 {
+        let (start, end) = f.domain();
+        let steps = 100_000;
+        let dx = (end - start) / f64::from(steps);
+        (0..steps)
+            .map(|i| {
+                let x = start + (f64::from(i) + 0.5) * dx;
+                f.y_at_x(x).unwrap().abs().powi(p) * dx
+            })
+            .sum()
+    }
+
+    #[test]
+    fn test_norms() {
+        let f = PiecewiseLinearFunction::try_from(vec![(0., 1.), (1., -3.), (2., -1.), (4., 2.)])
+            .unwrap();
+        // Triangles on each side of zero crossings, and a trapezoid.
+        assert_eq!(
+            f.norm_l1(),
+            0.125 + 1.125 + 2. + 0.5 * 2. / 3. * 1. + 0.5 * 4. / 3. * 2.
+        );
+        assert!((f.norm_l1() - numerical_integral(&f, 1)).abs() < 1e-6);
+        assert!((f.norm_l2() - numerical_integral(&f, 2).sqrt()).abs() < 1e-6);
+        assert_eq!(f.norm_linf(), 3.);
+
+        let ray = PiecewiseLinearFunction::with_edge_slopes(
+            vec![(0., 0.).into(), (1., 2.).into(), (2., 0.).into()],
+            Some(0.),
+            Some(0.),
+        )
+        .unwrap();
+        assert_eq!(ray.norm_l1(), 2.);
+        assert_eq!(ray.norm_linf(), 2.);
+        let growing = PiecewiseLinearFunction::constant((0., f64::INFINITY), 1.).unwrap();
+        assert_eq!(growing.norm_l1(), f64::INFINITY);
+        assert_eq!(growing.norm_l2(), f64::INFINITY);
+        assert_eq!(growing.norm_linf(), 1.);
+    }
+
+    #[test]
+    fn test_distances() {
+        let forecast =
+            PiecewiseLinearFunction::try_from(vec![(0., 0.), (2., 4.), (4., 2.)]).unwrap();
+        let realized =
+            PiecewiseLinearFunction::try_from(vec![(0., 1.), (1., 1.), (3., 4.), (4., 1.)])
+                .unwrap();
+        let difference = forecast.difference(&realized).unwrap();
+        assert_eq!(forecast.distance_linf(&realized), Some(1.5));
+        assert!(
+            (forecast.distance_l1(&realized).unwrap() - numerical_integral(&difference, 1)).abs()
+                < 1e-6
+        );
+        assert!(
+            (forecast.distance_l2(&realized).unwrap() - numerical_integral(&difference, 2).sqrt())
+                .abs()
+                < 1e-6
+        );
+        assert_eq!(forecast.distance_l1(&forecast), Some(0.));
+
+        let shorter = PiecewiseLinearFunction::try_from(vec![(0., 0.), (3., 1.)]).unwrap();
+        assert_eq!(forecast.distance_l2(&shorter), None);
+    }
+}
@@ -0,0 +1,298 @@
+// Copyright 2019 Matthieu Felix
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Periodic piecewise linear functions.
+
+use num_traits::Signed;
+
+use crate::{Coord, CoordFloat, PiecewiseLinearFunction};
+
+/// Largest multiple of a period that is tried when looking for a common period of two functions.
+const MAX_PERIOD_MULTIPLE: usize = 10_000;
+
+/// A continuous periodic piecewise linear function.
+///
+/// The function is defined by a `PiecewiseLinearFunction` over a single period, which is repeated
+/// over all real numbers. The start of the domain of that function sets the phase of the periodic
+/// function.
+///
+/// ## Example
+///
+/// ```
+/// use piecewise_linear::{PeriodicPiecewiseLinearFunction, PiecewiseLinearFunction};
+/// use std::convert::TryFrom;
+/// let f = PiecewiseLinearFunction::try_from(vec![(0., 0.), (12., 1.), (24., 0.)]).unwrap();
+/// let daily = PeriodicPiecewiseLinearFunction::new(f).unwrap();
+/// assert_eq!(daily.period(), 24.);
+/// assert_eq!(daily.y_at_x(24. * 3. + 6.), 0.5);
+/// ```
+#[derive(PartialEq, Clone, Debug)]
+pub struct PeriodicPiecewiseLinearFunction<T: CoordFloat> {
+    one_period: PiecewiseLinearFunction<T>,
+}
+
+impl<T: CoordFloat> PeriodicPiecewiseLinearFunction<T> {
+    /// Creates a new periodic function repeating `one_period`.
+    ///
+    /// Returns `None` if the domain of `one_period` is not bounded, or if its values at both ends
+    /// of its domain differ, as the periodic function would not be continuous.
+    pub fn new(one_period: PiecewiseLinearFunction<T>) -> Option<Self> {
+        let first = one_period.coordinates[0].y;
+        let last = one_period.coordinates.last().unwrap().y;
+        if one_period.is_bounded() && first == last {
+            Some(PeriodicPiecewiseLinearFunction { one_period })
+        } else {
+            None
+        }
+    }
+
+    /// Returns the function over a single period.
+    pub fn one_period(&self) -> &PiecewiseLinearFunction<T> {
+        &self.one_period
+    }
+
+    /// Returns the period of the function.
+    pub fn period(&self) -> T {
+        let (start, end) = self.one_period.domain();
+        end - start
+    }
+
+    /// Computes the value f(x) for this periodic function.
+    ///
+    /// Returns NaN if `x` is not finite.
+    pub fn y_at_x(&self, x: T) -> T {
+        if !x.is_finite() {
+            return T::nan();
+        }
+        let start = self.one_period.domain().0;
+        let period = self.period();
+        let offset = x - start;
+        let local_x = start + (offset - (offset / period).floor() * period);
+        // Rounding may push `local_x` just outside of the period.
+        self.one_period.y_at_x_clamped(local_x)
+    }
+
+    /// Returns a plain piecewise linear function that is equal to this function over `domain`.
+    ///
+    /// Returns `None` if `domain` is not bounded or not valid (i.e. `domain.1 <= domain.0`).
+    pub fn unroll(&self, domain: (T, T)) -> Option<PiecewiseLinearFunction<T>> {
+        if !(domain.0.is_finite() && domain.1.is_finite() && domain.0 < domain.1) {
+            return None;
+        }
+        let start = self.one_period.domain().0;
+        let period = self.period();
+        let first_period = ((domain.0 - start) / period).floor().to_i64()?;
+        let last_period = ((domain.1 - start) / period).ceil().to_i64()?;
+
+        let mut coordinates = Vec::new();
+        for k in first_period..last_period {
+            let shift = T::from(k)? * period;
+            let (_, head) = self.one_period.coordinates.split_last().unwrap();
+            coordinates.extend(head.iter().map(|c| Coord {
+                x: c.x + shift,
+                y: c.y,
+            }));
+        }
+        coordinates.push(Coord {
+            x: start + T::from(last_period)? * period,
+            y: self.one_period.coordinates[0].y,
+        });
+        PiecewiseLinearFunction::new(coordinates)?.shrink_domain(domain)
+    }
+
+    /// Returns the smallest common period of `self` and `other`, that is, their least common
+    /// multiple.
+    ///
+    /// Periods are compared up to rounding errors. Returns `None` if no common period is found
+    /// within a reasonable number of repetitions, for instance if the ratio of the two periods is
+    /// irrational.
+    pub fn common_period(&self, other: &PeriodicPiecewiseLinearFunction<T>) -> Option<T> {
+        let (period, other_period) = (self.period(), other.period());
+        let tolerance = T::epsilon() * T::from(MAX_PERIOD_MULTIPLE).unwrap();
+        (1..=MAX_PERIOD_MULTIPLE).find_map(|multiple| {
+            let candidate = period * T::from(multiple).unwrap();
+            let ratio = candidate / other_period;
+            let rounded = ratio.round();
+            if rounded >= T::one() && (ratio - rounded).abs() <= tolerance * ratio {
+                Some(candidate)
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Sums this function with another periodic function.
+    ///
+    /// The period of the result is the common period of both functions; returns `None` if there is
+    /// none (see `common_period()`).
+    pub fn add(
+        &self,
+        other: &PeriodicPiecewiseLinearFunction<T>,
+    ) -> Option<PeriodicPiecewiseLinearFunction<T>> {
+        self.combine(other, |f, g| f.add(g))
+    }
+
+    /// Returns a new periodic function that is the maximum of `self` and `other`.
+    ///
+    /// The period of the result is the common period of both functions; returns `None` if there is
+    /// none (see `common_period()`).
+    pub fn max(
+        &self,
+        other: &PeriodicPiecewiseLinearFunction<T>,
+    ) -> Option<PeriodicPiecewiseLinearFunction<T>> {
+        self.combine(other, |f, g| f.max(g))
+    }
+
+    /// Unrolls both functions over their common period and combines them with `op`.
+    fn combine<F>(
+        &self,
+        other: &PeriodicPiecewiseLinearFunction<T>,
+        op: F,
+    ) -> Option<PeriodicPiecewiseLinearFunction<T>>
+    where
+        F: Fn(
+            &PiecewiseLinearFunction<T>,
+            &PiecewiseLinearFunction<T>,
+        ) -> Option<PiecewiseLinearFunction<T>>,
+    {
+        let start = self.one_period.domain().0;
+        let domain = (start, start + self.common_period(other)?);
+        let mut combined = op(&self.unroll(domain)?, &other.unroll(domain)?)?;
+        // Both ends are equal up to rounding errors; make them exactly equal.
+        combined.coordinates.last_mut().unwrap().y = combined.coordinates[0].y;
+        PeriodicPiecewiseLinearFunction::new(combined)
+    }
+}
+
+impl<T: CoordFloat + Signed> PeriodicPiecewiseLinearFunction<T> {
+    /// Computes the minimum of this function and `other`.
+    ///
+    /// The period of the result is the common period of both functions; returns `None` if there is
+    /// none (see `common_period()`).
+    pub fn min(
+        &self,
+        other: &PeriodicPiecewiseLinearFunction<T>,
+    ) -> Option<PeriodicPiecewiseLinearFunction<T>> {
+        self.combine(other, |f, g| f.min(g))
+    }
+}
+
+impl<T: CoordFloat + ::std::iter::Sum> PeriodicPiecewiseLinearFunction<T> {
+    /// Returns the integral of the function over `[from, to]`.
+    ///
+    /// If `to < from`, the integral is negated, as usual. Returns NaN if either bound is not
+    /// finite.
+    pub fn integrate(&self, from: T, to: T) -> T {
+        if !(from.is_finite() && to.is_finite()) {
+            return T::nan();
+        }
+        if to < from {
+            return -self.integrate(to, from);
+        }
+        let period = self.period();
+        let full_periods = ((to - from) / period).floor();
+        let remainder_start = from + full_periods * period;
+        let remainder = self
+            .unroll((remainder_start, to))
+            .map_or(T::zero(), |f| f.integrate());
+        full_periods * self.one_period.integrate() + remainder
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use super::*;
+
+    fn get_daily_function() -> PeriodicPiecewiseLinearFunction<f64> {
+        PeriodicPiecewiseLinearFunction::new(
+            PiecewiseLinearFunction::try_from(vec![(0., 1.), (6., 1.), (12., 3.), (24., 1.)])
+                .unwrap(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_new() {
+        let discontinuous = PiecewiseLinearFunction::try_from(vec![(0., 0.), (1., 1.)]).unwrap();
+        assert_eq!(PeriodicPiecewiseLinearFunction::new(discontinuous), None);
+        let unbounded = PiecewiseLinearFunction::constant((0., f64::INFINITY), 1.).unwrap();
+        assert_eq!(PeriodicPiecewiseLinearFunction::new(unbounded), None);
+    }
+
+    #[test]
+    fn test_y_at_x() {
+        let f = get_daily_function();
+        assert_eq!(f.y_at_x(9.), 2.);
+        assert_eq!(f.y_at_x(24. + 9.), 2.);
+        assert_eq!(f.y_at_x(-24. * 7. + 9.), 2.);
+        assert_eq!(f.y_at_x(48.), 1.);
+        assert!(f.y_at_x(f64::INFINITY).is_nan());
+    }
+
+    #[test]
+    fn test_unroll() {
+        let f = get_daily_function();
+        assert_eq!(
+            f.unroll((9., 30.)).unwrap(),
+            PiecewiseLinearFunction::try_from(vec![(9., 2.), (12., 3.), (24., 1.), (30., 1.)])
+                .unwrap()
+        );
+        let unrolled = f.unroll((-50., 50.)).unwrap();
+        assert_eq!(unrolled.domain(), (-50., 50.));
+        for &x in &[-50., -37.5, -1., 0., 13., 49.] {
+            assert_eq!(unrolled.y_at_x(x).unwrap(), f.y_at_x(x));
+        }
+        assert_eq!(f.unroll((1., 1.)), None);
+    }
+
+    #[test]
+    fn test_integrate() {
+        let f = get_daily_function();
+        assert_eq!(f.integrate(0., 24.), 42.);
+        assert_eq!(f.integrate(0., 24. * 3. + 12.), 42. * 3. + 18.);
+        assert_eq!(f.integrate(6., 30.), 42.);
+        assert_eq!(f.integrate(30., 6.), -42.);
+        assert_eq!(f.integrate(3., 3.), 0.);
+    }
+
+    #[test]
+    fn test_add_max() {
+        let f = get_daily_function();
+        let g = PeriodicPiecewiseLinearFunction::new(
+            PiecewiseLinearFunction::try_from(vec![(0., 0.), (8., 4.), (16., 0.)]).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(f.common_period(&g), Some(48.));
+
+        let sum = f.add(&g).unwrap();
+        let max = f.max(&g).unwrap();
+        let min = f.min(&g).unwrap();
+        assert_eq!(sum.period(), 48.);
+        for &x in &[-13., 0., 5., 8., 17.5, 40., 100.] {
+            let (fx, gx) = (f.y_at_x(x), g.y_at_x(x));
+            assert!((sum.y_at_x(x) - (fx + gx)).abs() < 1e-12);
+            assert!((max.y_at_x(x) - fx.max(gx)).abs() < 1e-12);
+            assert!((min.y_at_x(x) - fx.min(gx)).abs() < 1e-12);
+        }
+
+        let irrational = PeriodicPiecewiseLinearFunction::new(
+            PiecewiseLinearFunction::try_from(vec![(0., 0.), (1., 1.), (std::f64::consts::PI, 0.)])
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(f.add(&irrational), None);
+    }
+}
@@ -0,0 +1,84 @@
+// Copyright 2019 Matthieu Felix
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Serde support, enabled by the `serde` feature.
+//!
+//! Functions are serialized as a sequence of `(x, y)` pairs, e.g. `[[0.0, 1.0], [2.0, 3.0]]` in
+//! JSON. Deserialization checks the invariants of `PiecewiseLinearFunction`.
+
+use serde::de::Error as _;
+use serde::ser::SerializeSeq;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{Coord, CoordFloat, PiecewiseLinearFunction};
+
+impl<T: CoordFloat + Serialize> Serialize for PiecewiseLinearFunction<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.coordinates.len()))?;
+        for c in &self.coordinates {
+            seq.serialize_element(&(c.x, c.y))?;
+        }
+        seq.end()
+    }
+}
+
+impl<'de, T: CoordFloat + Deserialize<'de>> Deserialize<'de> for PiecewiseLinearFunction<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let points = Vec::<(T, T)>::deserialize(deserializer)?;
+        PiecewiseLinearFunction::try_new(points.into_iter().map(|(x, y)| Coord { x, y }).collect())
+            .map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use super::*;
+    use crate::Error;
+
+    #[test]
+    fn test_round_trip() {
+        let f =
+            PiecewiseLinearFunction::try_from(vec![(0., 1.), (0.5, -2.), (3., 1e-300)]).unwrap();
+        let json = serde_json::to_string(&f).unwrap();
+        assert_eq!(json, "[[0.0,1.0],[0.5,-2.0],[3.0,1e-300]]");
+        assert_eq!(
+            serde_json::from_str::<PiecewiseLinearFunction<f64>>(&json).unwrap(),
+            f
+        );
+
+        let f32_function = PiecewiseLinearFunction::try_from(vec![(0f32, 1.), (1., 2.)]).unwrap();
+        let json = serde_json::to_string(&f32_function).unwrap();
+        assert_eq!(
+            serde_json::from_str::<PiecewiseLinearFunction<f32>>(&json).unwrap(),
+            f32_function
+        );
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        let unsorted = serde_json::from_str::<PiecewiseLinearFunction<f64>>("[[1, 0], [0, 1]]");
+        assert!(unsorted
+            .unwrap_err()
+            .to_string()
+            .contains(&Error::UnsortedPoints.to_string()));
+        let short = serde_json::from_str::<PiecewiseLinearFunction<f64>>("[[1, 0]]");
+        assert!(short
+            .unwrap_err()
+            .to_string()
+            .contains(&Error::TooFewPoints.to_string()));
+        assert!(serde_json::from_str::<PiecewiseLinearFunction<f64>>("[[1, 0, 2]]").is_err());
+    }
+}
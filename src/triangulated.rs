@@ -0,0 +1,721 @@
+// Copyright 2019 Matthieu Felix
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Bivariate piecewise linear functions, interpolated linearly on triangles.
+
+use std::collections::HashMap;
+
+use crate::{bogus_compare, Coord, CoordFloat, Error, PiecewiseLinearFunction};
+
+/// A continuous function of two variables, which is linear on each triangle of a triangulation of
+/// its domain.
+///
+/// The function is either defined on a rectilinear grid, each cell of which is split along the
+/// diagonal from its lower left corner to its upper right one, or on the Delaunay triangulation of
+/// a set of points. In both cases, its domain is convex.
+///
+/// ## Example
+///
+/// ```
+/// use piecewise_linear::{Coord, TriangulatedFunction};
+/// // f(x, y) = x + 2y, sampled on a 3x2 grid.
+/// let f = TriangulatedFunction::from_grid(&[0., 1., 2.], &[0., 1.], vec![0., 2., 1., 3., 2., 4.])
+///     .unwrap();
+/// assert_eq!(f.value_at(Coord { x: 1.5, y: 0.5 }), Some(2.5));
+/// assert_eq!(f.slice_at_y(1.).unwrap().y_at_x(0.5), Some(2.5));
+/// assert_eq!(f.integrate(), 4.);
+/// ```
+#[derive(PartialEq, Clone, Debug)]
+pub struct TriangulatedFunction<T: CoordFloat> {
+    vertices: Vec<Coord<T>>,
+    values: Vec<T>,
+    /// Indices of the vertices of each triangle, in counter-clockwise order.
+    triangles: Vec<[usize; 3]>,
+    /// The grid lines in x and y, for functions defined on a grid.
+    grid: Option<(Vec<T>, Vec<T>)>,
+}
+
+impl<T: CoordFloat> TriangulatedFunction<T> {
+    /// Creates a function on the grid with lines `xs` and `ys`, where the value at
+    /// `(xs[i], ys[j])` is `values[i * ys.len() + j]`.
+    ///
+    /// Returns `Error::TooFewPoints` if there are fewer than two lines in either direction,
+    /// `Error::UnsortedPoints` if they are not strictly increasing, `Error::UnboundedDomain` if
+    /// any of them is infinite, and `Error::DomainMismatch` if there is not one value per node.
+    pub fn from_grid(xs: &[T], ys: &[T], values: Vec<T>) -> Result<Self, Error> {
+        check_grid(xs, ys, values.len())?;
+        let vertices = xs
+            .iter()
+            .flat_map(|&x| ys.iter().map(move |&y| Coord { x, y }))
+            .collect();
+        let mut triangles = Vec::with_capacity(2 * (xs.len() - 1) * (ys.len() - 1));
+        for i in 0..xs.len() - 1 {
+            for j in 0..ys.len() - 1 {
+                let node = |i: usize, j: usize| i * ys.len() + j;
+                triangles.push([node(i, j), node(i + 1, j), node(i + 1, j + 1)]);
+                triangles.push([node(i, j), node(i + 1, j + 1), node(i, j + 1)]);
+            }
+        }
+        Ok(TriangulatedFunction {
+            vertices,
+            values,
+            triangles,
+            grid: Some((xs.to_vec(), ys.to_vec())),
+        })
+    }
+
+    /// Creates a function on the Delaunay triangulation of the given points and values, computed
+    /// with the Bowyer–Watson algorithm.
+    ///
+    /// Returns `Error::UnboundedDomain` if any point is not finite, and `Error::Degenerate` if
+    /// points are duplicated or all lie on a line.
+    ///
+    /// ## Complexity
+    ///
+    /// Building the triangulation takes _O(n²)_ time for _n_ points, and evaluating the function
+    /// takes _O(n)_ time.
+    pub fn delaunay(points: Vec<(Coord<T>, T)>) -> Result<Self, Error> {
+        if !points
+            .iter()
+            .all(|(c, _)| c.x.is_finite() && c.y.is_finite())
+        {
+            return Err(Error::UnboundedDomain);
+        }
+        let mut sorted: Vec<Coord<T>> = points.iter().map(|p| p.0).collect();
+        sorted.sort_by(|a, b| bogus_compare(&a.x, &b.x).then(bogus_compare(&a.y, &b.y)));
+        if points.len() < 3 || sorted.windows(2).any(|w| w[0] == w[1]) {
+            return Err(Error::Degenerate);
+        }
+        let (vertices, values): (Vec<_>, Vec<_>) = points.into_iter().unzip();
+        let triangles = bowyer_watson(&vertices);
+        if triangles.is_empty() {
+            return Err(Error::Degenerate);
+        }
+        Ok(TriangulatedFunction {
+            vertices,
+            values,
+            triangles,
+            grid: None,
+        })
+    }
+
+    /// Returns the number of triangles of the triangulation.
+    pub fn triangle_count(&self) -> usize {
+        self.triangles.len()
+    }
+
+    /// Computes the value f(p).
+    ///
+    /// Returns `None` if `p` is outside the domain of f. At the vertices of the triangulation,
+    /// the returned value is exactly the stored one.
+    pub fn value_at(&self, p: Coord<T>) -> Option<T> {
+        if let Some((xs, ys)) = &self.grid {
+            let i = grid_cell(xs, p.x)?;
+            let j = grid_cell(ys, p.y)?;
+            let u = (p.x - xs[i]) / (xs[i + 1] - xs[i]);
+            let v = (p.y - ys[j]) / (ys[j + 1] - ys[j]);
+            let cell = 2 * (i * (ys.len() - 1) + j);
+            let triangle = if u >= v { cell } else { cell + 1 };
+            // `p` is known to be in the triangle, so rounding errors must not exclude it.
+            return self.interpolate(triangle, p, T::infinity());
+        }
+        let tolerance = T::epsilon() * T::from(16).unwrap();
+        (0..self.triangles.len()).find_map(|t| self.interpolate(t, p, tolerance))
+    }
+
+    /// Returns the function along the segment from `from` to `to`, as a function of the
+    /// parameter `t` in [0, 1] such that the point is `from + t * (to - from)`.
+    ///
+    /// Returns `None` if the segment is not included in the domain, or if `from == to`.
+    pub fn slice_along(&self, from: Coord<T>, to: Coord<T>) -> Option<PiecewiseLinearFunction<T>> {
+        self.value_at(from)?;
+        self.value_at(to)?;
+        self.slice(from, to - from, Some((T::zero(), T::one())))
+    }
+
+    /// Returns the function y ↦ f(x, y) on the vertical line at `x`.
+    ///
+    /// Returns `None` if the line does not cross the domain, or only touches it at one point.
+    pub fn slice_at_x(&self, x: T) -> Option<PiecewiseLinearFunction<T>> {
+        self.slice(
+            Coord { x, y: T::zero() },
+            Coord {
+                x: T::zero(),
+                y: T::one(),
+            },
+            None,
+        )
+    }
+
+    /// Returns the function x ↦ f(x, y) on the horizontal line at `y`.
+    ///
+    /// Returns `None` if the line does not cross the domain, or only touches it at one point.
+    pub fn slice_at_y(&self, y: T) -> Option<PiecewiseLinearFunction<T>> {
+        self.slice(
+            Coord { x: T::zero(), y },
+            Coord {
+                x: T::one(),
+                y: T::zero(),
+            },
+            None,
+        )
+    }
+
+    /// Returns the maximum value of the function, and a point where it is reached.
+    pub fn max(&self) -> (Coord<T>, T) {
+        self.extremum(|a, b| a > b)
+    }
+
+    /// Returns the minimum value of the function, and a point where it is reached.
+    pub fn min(&self) -> (Coord<T>, T) {
+        self.extremum(|a, b| a < b)
+    }
+
+    /// Returns the integral of the function over its domain.
+    pub fn integrate(&self) -> T {
+        let three = T::from(3).unwrap();
+        self.triangles
+            .iter()
+            .map(|&[a, b, c]| {
+                let area = cross(
+                    self.vertices[b] - self.vertices[a],
+                    self.vertices[c] - self.vertices[a],
+                ) / (T::one() + T::one());
+                area * (self.values[a] + self.values[b] + self.values[c]) / three
+            })
+            .fold(T::zero(), |total, part| total + part)
+    }
+
+    /// Returns the vertex for which `better` is true against all the others.
+    fn extremum(&self, better: impl Fn(T, T) -> bool) -> (Coord<T>, T) {
+        let mut best = 0;
+        for (i, &value) in self.values.iter().enumerate() {
+            if better(value, self.values[best]) {
+                best = i;
+            }
+        }
+        (self.vertices[best], self.values[best])
+    }
+
+    /// Interpolates linearly within `triangle` at `p`, or returns `None` if `p` is outside of it
+    /// by more than `tolerance` in barycentric coordinates.
+    fn interpolate(&self, triangle: usize, p: Coord<T>, tolerance: T) -> Option<T> {
+        let weights = self.barycentric_weights(triangle, p);
+        if weights.iter().any(|&w| w < -tolerance) {
+            return None;
+        }
+        Some(self.combine(triangle, weights))
+    }
+
+    /// Interpolates at a point `p` of the boundary of the domain, or just outside of it because
+    /// of rounding errors, by clamping it to the triangle from which it is the closest in
+    /// barycentric coordinates.
+    fn interpolate_clamped(&self, p: Coord<T>) -> T {
+        let (triangle, weights) = (0..self.triangles.len())
+            .map(|t| (t, self.barycentric_weights(t, p)))
+            .fold(None, |best: Option<(usize, [T; 3])>, (t, weights)| {
+                let margin = |w: &[T; 3]| w.iter().fold(T::infinity(), |m, &x| m.min(x));
+                match best {
+                    Some(best) if margin(&best.1) >= margin(&weights) => Some(best),
+                    _ => Some((t, weights)),
+                }
+            })
+            // This unwrap is guaranteed to succeed as there is at least one triangle.
+            .unwrap();
+        let clamped = weights.map(|w| w.max(T::zero()));
+        let total = clamped[0] + clamped[1] + clamped[2];
+        self.combine(triangle, clamped.map(|w| w / total))
+    }
+
+    /// Returns the barycentric coordinates of `p` relative to the vertices of `triangle`.
+    fn barycentric_weights(&self, triangle: usize, p: Coord<T>) -> [T; 3] {
+        let [a, b, c] = self.triangles[triangle];
+        let (va, vb, vc) = (self.vertices[a], self.vertices[b], self.vertices[c]);
+        let area = cross(vb - va, vc - va);
+        [
+            cross(vb - p, vc - p) / area,
+            cross(vc - p, va - p) / area,
+            cross(va - p, vb - p) / area,
+        ]
+    }
+
+    /// Combines the values at the vertices of `triangle` with `weights`.
+    fn combine(&self, triangle: usize, weights: [T; 3]) -> T {
+        // Skip the other terms so that values at vertices are exact.
+        self.triangles[triangle]
+            .iter()
+            .zip(&weights)
+            .filter(|(_, w)| **w != T::zero())
+            .fold(T::zero(), |total, (&v, &w)| total + w * self.values[v])
+    }
+
+    /// Returns the function along the line through `origin` with direction `direction`, as a
+    /// function of the parameter `s` such that the point is `origin + s * direction`, restricted
+    /// to `bounds` if given.
+    fn slice(
+        &self,
+        origin: Coord<T>,
+        direction: Coord<T>,
+        bounds: Option<(T, T)>,
+    ) -> Option<PiecewiseLinearFunction<T>> {
+        if direction == Coord::zero() {
+            return None;
+        }
+        // Parameters at which the line crosses the edges of the triangles.
+        let mut params = Vec::new();
+        let norm = direction.x * direction.x + direction.y * direction.y;
+        for triangle in &self.triangles {
+            for k in 0..3 {
+                let p = self.vertices[triangle[k]];
+                let q = self.vertices[triangle[(k + 1) % 3]];
+                let edge = q - p;
+                let denominator = cross(direction, edge);
+                if denominator == T::zero() {
+                    if cross(direction, p - origin) == T::zero() {
+                        // The edge lies on the line.
+                        params.push(dot(p - origin, direction) / norm);
+                        params.push(dot(q - origin, direction) / norm);
+                    }
+                    continue;
+                }
+                let u = cross(origin - p, direction) / cross(edge, direction);
+                if u >= T::zero() && u <= T::one() {
+                    params.push(cross(p - origin, edge) / denominator);
+                }
+            }
+        }
+        if let Some((start, end)) = bounds {
+            params.retain(|s| *s > start && *s < end);
+            params.push(start);
+            params.push(end);
+        }
+        params.sort_by(bogus_compare);
+        params.dedup();
+        // All parameters are on the domain, which is convex, so points found outside of it because
+        // of rounding errors are clamped back onto its boundary.
+        let coordinates = params
+            .into_iter()
+            .map(|s| {
+                let point = origin + direction * s;
+                let y = self
+                    .value_at(point)
+                    .unwrap_or_else(|| self.interpolate_clamped(point));
+                Coord { x: s, y }
+            })
+            .collect();
+        PiecewiseLinearFunction::new(coordinates)
+    }
+}
+
+/// Checks that `xs` and `ys` are grid lines with `values_len` nodes, returning the errors
+/// described on `TriangulatedFunction::from_grid()`.
+pub(crate) fn check_grid<T: CoordFloat>(
+    xs: &[T],
+    ys: &[T],
+    values_len: usize,
+) -> Result<(), Error> {
+    for lines in &[xs, ys] {
+        if lines.len() < 2 {
+            return Err(Error::TooFewPoints);
+        } else if !lines.windows(2).all(|w| w[0] < w[1]) {
+            return Err(Error::UnsortedPoints);
+        } else if !lines.iter().all(|l| l.is_finite()) {
+            return Err(Error::UnboundedDomain);
+        }
+    }
+    if values_len != xs.len() * ys.len() {
+        return Err(Error::DomainMismatch);
+    }
+    Ok(())
+}
+
+/// Returns the index `i` such that `lines[i] <= x <= lines[i + 1]`, or `None` if `x` is outside
+/// of the grid.
+pub(crate) fn grid_cell<T: CoordFloat>(lines: &[T], x: T) -> Option<usize> {
+    // This unwrap is guaranteed to succeed as grids have at least two lines.
+    if !(lines[0] <= x && x <= *lines.last().unwrap()) {
+        return None;
+    }
+    let idx = lines.partition_point(|l| *l <= x);
+    Some(idx.saturating_sub(1).min(lines.len() - 2))
+}
+
+fn cross<T: CoordFloat>(a: Coord<T>, b: Coord<T>) -> T {
+    a.x * b.y - a.y * b.x
+}
+
+fn dot<T: CoordFloat>(a: Coord<T>, b: Coord<T>) -> T {
+    a.x * b.x + a.y * b.y
+}
+
+/// The vertex at infinity shared by all ghost triangles.
+const GHOST: usize = usize::MAX;
+
+/// Computes the Delaunay triangulation of distinct points, as counter-clockwise triangles of
+/// indices into `points`. Returns no triangles if all the points lie on a line.
+///
+/// Instead of a finite super-triangle, whose vertices would have to be infinitely far away not to
+/// affect the hull, each edge of the hull is closed by a ghost triangle with a vertex at
+/// infinity. A point is in conflict with a ghost triangle if it lies outside of its edge, or on
+/// the edge itself. Triangles are stored as a map from each of their directed edges to the
+/// opposite vertex.
+fn bowyer_watson<T: CoordFloat>(points: &[Coord<T>]) -> Vec<[usize; 3]> {
+    let orientation =
+        |a: usize, b: usize, c: usize| cross(points[b] - points[a], points[c] - points[a]);
+    // Points are distinct, so the first two points and any point off their line form a triangle.
+    let third = match (2..points.len()).find(|&k| orientation(0, 1, k) != T::zero()) {
+        Some(third) => third,
+        None => return Vec::new(),
+    };
+    let first = if orientation(0, 1, third) > T::zero() {
+        [0, 1, third]
+    } else {
+        [1, 0, third]
+    };
+    let mut edges = HashMap::new();
+    insert_triangle(&mut edges, first);
+    for k in 0..3 {
+        insert_triangle(&mut edges, [first[(k + 1) % 3], first[k], GHOST]);
+    }
+
+    for i in (2..points.len()).filter(|&i| i != third) {
+        let p = points[i];
+        let conflicts = |t: [usize; 3]| {
+            let (a, b) = (points[t[0]], points[t[1]]);
+            if t[2] == GHOST {
+                let side = orientation(t[0], t[1], i);
+                side > T::zero() || (side == T::zero() && dot(a - p, b - p) < T::zero())
+            } else {
+                in_circumcircle(a, b, points[t[2]], p)
+            }
+        };
+        // The cavity grows from the triangle containing `p`, or from a ghost triangle if `p` is
+        // outside of the hull, so that it stays connected.
+        let seed = triangles(&edges).find(|&t| {
+            if t[2] == GHOST {
+                conflicts(t)
+            } else {
+                (0..3).all(|k| orientation(t[k], t[(k + 1) % 3], i) >= T::zero())
+            }
+        });
+        let mut cavity = match seed {
+            Some(seed) => vec![seed],
+            // Only possible through rounding errors.
+            None => continue,
+        };
+        let mut boundary = Vec::new();
+        let mut next = 0;
+        while next < cavity.len() {
+            let t = cavity[next];
+            next += 1;
+            for k in 0..3 {
+                let (a, b) = (t[k], t[(k + 1) % 3]);
+                // Every edge has a neighbour, as ghost triangles close the hull.
+                let neighbour = normalize([b, a, edges[&(b, a)]]);
+                if cavity.contains(&neighbour) {
+                    continue;
+                } else if conflicts(neighbour) {
+                    cavity.push(neighbour);
+                } else {
+                    boundary.push((a, b));
+                }
+            }
+        }
+        for t in &cavity {
+            for k in 0..3 {
+                edges.remove(&(t[k], t[(k + 1) % 3]));
+            }
+        }
+        for (a, b) in boundary {
+            insert_triangle(&mut edges, [a, b, i]);
+        }
+    }
+
+    let mut result: Vec<[usize; 3]> = triangles(&edges).filter(|t| t[2] != GHOST).collect();
+    result.sort_unstable();
+    result
+}
+
+/// Rotates a triangle so that its greatest vertex, which is `GHOST` for ghost triangles, is last.
+fn normalize(t: [usize; 3]) -> [usize; 3] {
+    if t[0] > t[1] && t[0] > t[2] {
+        [t[1], t[2], t[0]]
+    } else if t[1] > t[2] {
+        [t[2], t[0], t[1]]
+    } else {
+        t
+    }
+}
+
+fn insert_triangle(edges: &mut HashMap<(usize, usize), usize>, t: [usize; 3]) {
+    for k in 0..3 {
+        edges.insert((t[k], t[(k + 1) % 3]), t[(k + 2) % 3]);
+    }
+}
+
+/// Iterates over the triangles stored in `edges`, each exactly once and normalized.
+fn triangles(edges: &HashMap<(usize, usize), usize>) -> impl Iterator<Item = [usize; 3]> + '_ {
+    edges.iter().filter_map(|(&(a, b), &c)| {
+        let t = normalize([a, b, c]);
+        if (t[0], t[1]) == (a, b) {
+            Some(t)
+        } else {
+            None
+        }
+    })
+}
+
+/// Checks whether `p` is strictly inside the circumcircle of the counter-clockwise triangle
+/// `(a, b, c)`.
+fn in_circumcircle<T: CoordFloat>(a: Coord<T>, b: Coord<T>, c: Coord<T>, p: Coord<T>) -> bool {
+    let (a, b, c) = (a - p, b - p, c - p);
+    let (la, lb, lc) = (dot(a, a), dot(b, b), dot(c, c));
+    let determinant = la * cross(b, c) - lb * cross(a, c) + lc * cross(a, b);
+    determinant > T::zero()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use super::*;
+
+    fn coord(x: f64, y: f64) -> Coord<f64> {
+        Coord { x, y }
+    }
+
+    /// f(x, y) = x + 2y + 1 on a 3x3 grid over [0, 2] x [0, 1].
+    fn get_grid_function() -> TriangulatedFunction<f64> {
+        let (xs, ys) = ([0., 1., 2.], [0., 0.5, 1.]);
+        let values = xs
+            .iter()
+            .flat_map(|x| ys.iter().map(move |y| x + 2. * y + 1.))
+            .collect();
+        TriangulatedFunction::from_grid(&xs, &ys, values).unwrap()
+    }
+
+    #[test]
+    fn test_from_grid() {
+        assert_eq!(
+            TriangulatedFunction::from_grid(&[0.], &[0., 1.], vec![0., 0.]),
+            Err(Error::TooFewPoints)
+        );
+        assert_eq!(
+            TriangulatedFunction::from_grid(&[0., 1.], &[1., 0.], vec![0.; 4]),
+            Err(Error::UnsortedPoints)
+        );
+        assert_eq!(
+            TriangulatedFunction::from_grid(&[0., 1.], &[0., 1.], vec![0.; 3]),
+            Err(Error::DomainMismatch)
+        );
+        let f = get_grid_function();
+        assert_eq!(f.triangle_count(), 8);
+        assert_eq!(f.value_at(coord(1., 0.5)), Some(3.));
+        assert_eq!(f.value_at(coord(0.25, 0.75)), Some(2.75));
+        assert_eq!(f.value_at(coord(2., 1.)), Some(5.));
+        assert_eq!(f.value_at(coord(2.1, 1.)), None);
+        assert_eq!(f.value_at(coord(0., -0.1)), None);
+    }
+
+    #[test]
+    fn test_grid_triangulation() {
+        // A saddle, whose interpolation depends on the diagonal.
+        let f =
+            TriangulatedFunction::from_grid(&[0., 1.], &[0., 1.], vec![0., 1., 1., 0.]).unwrap();
+        assert_eq!(f.value_at(coord(0.5, 0.5)), Some(0.));
+        assert_eq!(f.value_at(coord(0.75, 0.25)), Some(0.5));
+        assert_eq!(f.integrate(), 1. / 3.);
+    }
+
+    #[test]
+    fn test_slices() {
+        let f = get_grid_function();
+        // The line crosses the diagonals of the cells at x = 0.5 and x = 1.5.
+        let horizontal = f.slice_at_y(0.25).unwrap();
+        let expected = [(0., 1.5), (0.5, 2.), (1., 2.5), (1.5, 3.), (2., 3.5)];
+        assert_eq!(horizontal.coordinates.len(), expected.len());
+        for (c, &(x, y)) in horizontal.coordinates.iter().zip(&expected) {
+            assert_eq!(c.x, x);
+            assert!((c.y - y).abs() < 1e-12);
+        }
+        // On a grid line, the values are those of the nodes.
+        assert_eq!(
+            f.slice_at_y(0.5).unwrap(),
+            PiecewiseLinearFunction::try_from(vec![(0., 2.), (1., 3.), (2., 4.)]).unwrap()
+        );
+        let vertical = f.slice_at_x(0.5).unwrap();
+        assert_eq!(vertical.domain(), (0., 1.));
+        assert_eq!(vertical.y_at_x(0.5), Some(2.5));
+        assert_eq!(f.slice_at_x(3.), None);
+        // f is constant along this diagonal.
+        let diagonal = f.slice_along(coord(0., 1.), coord(2., 0.)).unwrap();
+        assert_eq!(diagonal.domain(), (0., 1.));
+        for &(t, y) in &[(0., 3.), (0.3, 3.), (0.5, 3.), (1., 3.)] {
+            assert!((diagonal.y_at_x(t).unwrap() - y).abs() < 1e-12);
+        }
+        assert_eq!(f.slice_along(coord(0., 0.), coord(3., 0.)), None);
+    }
+
+    #[test]
+    fn test_slices_on_boundary() {
+        // Far from the origin, points on the slanted edges are often outside of the triangle
+        // after rounding, and must not be dropped.
+        let (x0, y0) = (1e6, 1e6);
+        let linear = |p: Coord<f64>| (p.x - x0) + 2. * (p.y - y0);
+        let vertices = [coord(x0, y0), coord(x0 + 1., y0), coord(x0 + 0.3, y0 + 1.)];
+        let f = TriangulatedFunction::delaunay(vertices.iter().map(|&p| (p, linear(p))).collect())
+            .unwrap();
+        for i in 1..100 {
+            let t = f64::from(i) / 100.;
+            let x = x0 + t;
+            let top = if t < 0.3 { t / 0.3 } else { (1. - t) / 0.7 };
+            let vertical = f.slice_at_x(x).unwrap();
+            assert_eq!(vertical.coordinates.len(), 2);
+            assert!((vertical.domain().0 - y0).abs() < 1e-9);
+            assert!((vertical.domain().1 - y0 - top).abs() < 1e-9);
+            for c in &vertical.coordinates {
+                assert!((c.y - linear(coord(x, c.x))).abs() < 1e-6);
+            }
+
+            let y = y0 + t;
+            let horizontal = f.slice_at_y(y).unwrap();
+            assert_eq!(horizontal.coordinates.len(), 2);
+            assert!((horizontal.domain().0 - x0 - 0.3 * t).abs() < 1e-9);
+            assert!((horizontal.domain().1 - x0 - 1. + 0.7 * t).abs() < 1e-9);
+            for c in &horizontal.coordinates {
+                assert!((c.y - linear(coord(c.x, y))).abs() < 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    fn test_extrema_and_integral() {
+        let f = get_grid_function();
+        assert_eq!(f.max(), (coord(2., 1.), 5.));
+        assert_eq!(f.min(), (coord(0., 0.), 1.));
+        // The mean of x + 2y + 1 is 3 over the domain of area 2.
+        assert!((f.integrate() - 6.).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_delaunay() {
+        assert_eq!(
+            TriangulatedFunction::delaunay(vec![(coord(0., 0.), 0.), (coord(1., 1.), 0.)]),
+            Err(Error::Degenerate)
+        );
+        assert_eq!(
+            TriangulatedFunction::delaunay(vec![
+                (coord(0., 0.), 0.),
+                (coord(1., 1.), 0.),
+                (coord(2., 2.), 0.),
+            ]),
+            Err(Error::Degenerate)
+        );
+        assert_eq!(
+            TriangulatedFunction::delaunay(vec![
+                (coord(0., 0.), 0.),
+                (coord(1., 0.), 0.),
+                (coord(1., 0.), 0.),
+            ]),
+            Err(Error::Degenerate)
+        );
+
+        // A linear function is interpolated exactly.
+        let linear = |p: Coord<f64>| 3. * p.x - p.y + 2.;
+        let points = [
+            coord(0., 0.),
+            coord(4., 0.),
+            coord(4., 3.),
+            coord(0., 3.),
+            coord(1., 1.),
+            coord(3., 2.),
+            coord(2., 0.5),
+        ];
+        let f = TriangulatedFunction::delaunay(points.iter().map(|&p| (p, linear(p))).collect())
+            .unwrap();
+        // A triangulation of n points with h on the hull has 2n - h - 2 triangles.
+        assert_eq!(f.triangle_count(), 8);
+        for &p in &[
+            coord(0.5, 2.5),
+            coord(3.9, 0.1),
+            coord(2., 1.5),
+            coord(4., 3.),
+        ] {
+            assert!((f.value_at(p).unwrap() - linear(p)).abs() < 1e-12);
+        }
+        assert_eq!(f.value_at(coord(4.5, 1.)), None);
+        assert!((f.integrate() - 12. * linear(coord(2., 1.5))).abs() < 1e-9);
+        assert_eq!(f.max(), (coord(4., 0.), 14.));
+        let slice = f.slice_at_y(1.5).unwrap();
+        assert_eq!(slice.domain(), (0., 4.));
+        assert!((slice.y_at_x(2.5).unwrap() - linear(coord(2.5, 1.5))).abs() < 1e-12);
+    }
+
+    /// Checks that the triangulation of `points`, `h` of which are on the hull of area `area`,
+    /// has the expected number of triangles and covers the hull exactly once.
+    fn check_triangulation(points: &[Coord<f64>], h: usize, area: f64) {
+        let f = TriangulatedFunction::delaunay(points.iter().map(|&p| (p, 1.)).collect()).unwrap();
+        assert_eq!(f.triangle_count(), 2 * points.len() - h - 2);
+        assert!((f.integrate() - area).abs() < 1e-9 * area);
+        for t in &f.triangles {
+            let [a, b, c] = t.map(|v| f.vertices[v]);
+            assert!(cross(b - a, c - a) > 0.);
+        }
+    }
+
+    #[test]
+    fn test_delaunay_cocircular() {
+        for &n in &[4, 6, 8, 16, 31] {
+            let mut points = vec![coord(0., 0.)];
+            for k in 0..n {
+                let angle = 2. * std::f64::consts::PI * k as f64 / n as f64;
+                points.push(coord(angle.cos(), angle.sin()));
+            }
+            let area = n as f64 / 2. * (2. * std::f64::consts::PI / n as f64).sin();
+            check_triangulation(&points, n, area);
+            // Without the center, all the points are on the same circle.
+            check_triangulation(&points[1..], n, area);
+        }
+        // A grid, whose cells have cocircular corners and whose sides have collinear points.
+        let grid: Vec<_> = (0..16)
+            .map(|k| coord(f64::from(k % 4), f64::from(k / 4)))
+            .collect();
+        check_triangulation(&grid, 12, 9.);
+    }
+
+    #[test]
+    fn test_delaunay_thin() {
+        let points = [
+            coord(0., 0.),
+            coord(100., 0.),
+            coord(50., 0.01),
+            coord(25., 0.001),
+            coord(75., 0.001),
+        ];
+        check_triangulation(&points, 3, 0.5);
+        let f = TriangulatedFunction::delaunay(points.iter().map(|&p| (p, p.x)).collect()).unwrap();
+        for &p in &[coord(50., 0.005), coord(1., 0.00001), coord(99., 0.00001)] {
+            assert!((f.value_at(p).unwrap() - p.x).abs() < 1e-9);
+        }
+        // Points added on the line through the first two, outside of their segment.
+        let line = [
+            coord(0., 0.),
+            coord(1., 0.),
+            coord(-1., 0.),
+            coord(2., 0.),
+            coord(0.5, 1e-3),
+        ];
+        check_triangulation(&line, 5, 1.5e-3);
+    }
+}
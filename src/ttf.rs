@@ -0,0 +1,328 @@
+// Copyright 2019 Matthieu Felix
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Travel time functions for routing in time-dependent networks.
+//!
+//! A travel time function maps the departure time `t` from the start of an edge to the time it
+//! takes to traverse it. Travel time functions are expected to respect the FIFO property: leaving
+//! later never makes one arrive earlier, i.e. the arrival time `t + f(t)` is non-decreasing.
+//!
+//! Paths are evaluated by linking the functions of consecutive edges, and alternative paths by
+//! merging their functions. The result of `link()` has at most _n + m_ points, and the result of
+//! `merge()` at most _n + m_ plus one point per crossing. When these operations are chained over
+//! many edges, `simplify()` keeps the number of points in check with a bounded error.
+//!
+//! ## Example
+//!
+//! ```
+//! use piecewise_linear::ttf::TravelTimeFunction;
+//! use piecewise_linear::PiecewiseLinearFunction;
+//! use std::convert::TryFrom;
+//! // The first road is congested around t = 10.
+//! let first = TravelTimeFunction::new(
+//!     PiecewiseLinearFunction::try_from(vec![(0., 5.), (10., 10.), (20., 5.)]).unwrap(),
+//! )
+//! .unwrap();
+//! let second = TravelTimeFunction::new(
+//!     PiecewiseLinearFunction::constant((0., 40.), 3.).unwrap(),
+//! )
+//! .unwrap();
+//! let path = first.link(&second).unwrap();
+//! assert_eq!(path.travel_time_at(10.), Some(13.));
+//! ```
+
+use crate::envelope::{remove_collinear_points, Envelope, Extremum};
+use crate::{y_at_x, Coord, CoordFloat, Line, PiecewiseLinearFunction};
+
+/// A travel time function over a bounded range of departure times, which respects the FIFO
+/// property.
+#[derive(PartialEq, Clone, Debug)]
+pub struct TravelTimeFunction<T: CoordFloat> {
+    travel_time: PiecewiseLinearFunction<T>,
+}
+
+impl<T: CoordFloat> TravelTimeFunction<T> {
+    /// Creates a new travel time function from the travel time at each departure time.
+    ///
+    /// Returns `None` if the domain of `travel_time` is not bounded, or if it does not respect the
+    /// FIFO property, i.e. if any of its slopes is less than -1.
+    pub fn new(travel_time: PiecewiseLinearFunction<T>) -> Option<Self> {
+        let fifo = travel_time.segments_iter().all(|s| s.slope() >= -T::one());
+        if travel_time.is_bounded() && fifo {
+            Some(TravelTimeFunction { travel_time })
+        } else {
+            None
+        }
+    }
+
+    /// Returns the travel time as a function of the departure time.
+    pub fn travel_time(&self) -> &PiecewiseLinearFunction<T> {
+        &self.travel_time
+    }
+
+    /// Returns the range of departure times for which this function is defined.
+    pub fn domain(&self) -> (T, T) {
+        self.travel_time.domain()
+    }
+
+    /// Returns the travel time when departing at `t`, or `None` if `t` is outside of the domain.
+    pub fn travel_time_at(&self, t: T) -> Option<T> {
+        self.travel_time.y_at_x(t)
+    }
+
+    /// Returns the arrival time when departing at `t`, or `None` if `t` is outside of the domain.
+    pub fn arrival_time_at(&self, t: T) -> Option<T> {
+        self.travel_time_at(t).map(|travel_time| t + travel_time)
+    }
+
+    /// Returns the arrival time as a function of the departure time, which is non-decreasing.
+    pub fn arrival_function(&self) -> PiecewiseLinearFunction<T> {
+        PiecewiseLinearFunction::new(
+            self.travel_time
+                .coordinates
+                .iter()
+                .map(|c| Coord {
+                    x: c.x,
+                    y: c.x + c.y,
+                })
+                .collect(),
+        )
+        // This unwrap is guaranteed to succeed because the coordinate's x values haven't changed.
+        .unwrap()
+    }
+
+    /// Links this function with the function `next` of the following edge, that is, computes the
+    /// travel time function t -> f(t) + g(t + f(t)) of the path made of both edges.
+    ///
+    /// The domain of the result is the range of departure times for which the arrival time is in
+    /// the domain of `next`. Returns `None` if this range has less than two points.
+    pub fn link(&self, next: &TravelTimeFunction<T>) -> Option<TravelTimeFunction<T>> {
+        let arrival = self.arrival_function();
+        let (next_start, next_end) = next.domain();
+        let next_points: Vec<T> = next.travel_time.coordinates.iter().map(|c| c.x).collect();
+
+        // The departure times at which either function has a point of inflection.
+        let mut departures: Vec<T> =
+            Vec::with_capacity(arrival.coordinates.len() + next_points.len());
+        let mut push = |t: T| {
+            if !matches!(departures.last(), Some(&last) if last >= t) {
+                departures.push(t);
+            }
+        };
+        let mut j = 0;
+        for segment in arrival.segments_iter() {
+            let (p, q) = (segment.start, segment.end);
+            if q.y < next_start || p.y > next_end {
+                continue;
+            }
+            let departure_at = |a: T| {
+                if q.y == p.y {
+                    p.x
+                } else {
+                    p.x + (a - p.y) / (q.y - p.y) * (q.x - p.x)
+                }
+            };
+            let (lo, hi) = (p.y.max(next_start), q.y.min(next_end));
+            push(if p.y >= next_start {
+                p.x
+            } else {
+                departure_at(next_start)
+            });
+            while j < next_points.len() && next_points[j] <= lo {
+                j += 1;
+            }
+            while j < next_points.len() && next_points[j] < hi {
+                push(departure_at(next_points[j]));
+                j += 1;
+            }
+            push(if q.y <= next_end {
+                q.x
+            } else {
+                departure_at(next_end)
+            });
+        }
+
+        let mut coordinates: Vec<Coord<T>> = Vec::with_capacity(departures.len());
+        let mut last_arrival = T::neg_infinity();
+        for t in departures {
+            let intermediate = self.arrival_time_at(t)?;
+            let intermediate = intermediate.max(next_start).min(next_end);
+            // Rounding errors must not break the FIFO property.
+            let final_arrival =
+                (intermediate + next.travel_time.y_at_x_clamped(intermediate)).max(last_arrival);
+            last_arrival = final_arrival;
+            coordinates.push(Coord {
+                x: t,
+                y: final_arrival - t,
+            });
+        }
+        let travel_time = PiecewiseLinearFunction::new(remove_collinear_points(coordinates))?;
+        Some(TravelTimeFunction { travel_time })
+    }
+
+    /// Merges this function with the function `other` of an alternative path, that is, computes
+    /// the minimum of both travel times at each departure time.
+    ///
+    /// The domain of the result is the intersection of both domains, where both paths can be
+    /// taken. Returns `None` if they overlap in at most one point.
+    pub fn merge(&self, other: &TravelTimeFunction<T>) -> Option<TravelTimeFunction<T>> {
+        let (domain, other_domain) = (self.domain(), other.domain());
+        let common = (domain.0.max(other_domain.0), domain.1.min(other_domain.1));
+        if common.0 >= common.1 {
+            return None;
+        }
+        let mut envelope = Envelope::new(Extremum::Lower);
+        envelope.insert_function(&self.travel_time.shrink_domain(common)?);
+        envelope.insert_function(&other.travel_time.shrink_domain(common)?);
+        // The minimum of two FIFO functions is FIFO.
+        Some(TravelTimeFunction {
+            travel_time: envelope.into_function()?,
+        })
+    }
+
+    /// Returns an approximation of this function with fewer points, which differs from it by at
+    /// most `max_error` at every departure time.
+    ///
+    /// The points of the result are a subset of the points of this function, chosen with the
+    /// Ramer–Douglas–Peucker algorithm on vertical distances. The result respects the FIFO
+    /// property, as any chord of a non-decreasing arrival function is non-decreasing.
+    pub fn simplify(&self, max_error: T) -> TravelTimeFunction<T> {
+        let points = &self.travel_time.coordinates;
+        let mut keep = vec![false; points.len()];
+        keep[0] = true;
+        keep[points.len() - 1] = true;
+        let mut ranges = vec![(0, points.len() - 1)];
+        while let Some((first, last)) = ranges.pop() {
+            let chord = Line::new(points[first], points[last]);
+            let farthest = (first + 1..last)
+                .map(|i| (i, (y_at_x(&chord, points[i].x) - points[i].y).abs()))
+                .fold(None, |best: Option<(usize, T)>, (i, error)| match best {
+                    Some((_, best_error)) if best_error >= error => best,
+                    _ => Some((i, error)),
+                });
+            if let Some((i, error)) = farthest {
+                if error > max_error {
+                    keep[i] = true;
+                    ranges.push((first, i));
+                    ranges.push((i, last));
+                }
+            }
+        }
+        let coordinates = points
+            .iter()
+            .zip(keep)
+            .filter(|&(_, keep)| keep)
+            .map(|(&c, _)| c)
+            .collect();
+        TravelTimeFunction {
+            // This unwrap is guaranteed to succeed because both edge points are kept.
+            travel_time: PiecewiseLinearFunction::new(coordinates).unwrap(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use super::*;
+
+    fn ttf(points: Vec<(f64, f64)>) -> TravelTimeFunction<f64> {
+        TravelTimeFunction::new(PiecewiseLinearFunction::try_from(points).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_new() {
+        let overtaking = PiecewiseLinearFunction::try_from(vec![(0., 10.), (1., 8.)]).unwrap();
+        assert_eq!(TravelTimeFunction::new(overtaking), None);
+        let unbounded = PiecewiseLinearFunction::constant((0., f64::INFINITY), 1.).unwrap();
+        assert_eq!(TravelTimeFunction::new(unbounded), None);
+        let f = ttf(vec![(0., 10.), (5., 5.), (10., 8.)]);
+        assert_eq!(f.arrival_time_at(5.), Some(10.));
+        assert_eq!(f.arrival_time_at(11.), None);
+    }
+
+    #[test]
+    fn test_link() {
+        let f = ttf(vec![(0., 10.), (10., 5.), (20., 10.)]);
+        let g = ttf(vec![(0., 2.), (15., 8.), (18., 5.), (40., 2.)]);
+        let linked = f.link(&g).unwrap();
+        assert_eq!(linked.domain(), (0., 20.));
+        for i in 0..=200 {
+            let t = f64::from(i) / 10.;
+            let expected = f.travel_time_at(t).unwrap()
+                + g.travel_time_at(f.arrival_time_at(t).unwrap()).unwrap();
+            assert!((linked.travel_time_at(t).unwrap() - expected).abs() < 1e-12);
+        }
+        // One point from `f`, one from `g`, and both edges.
+        assert_eq!(linked.travel_time().coordinates.len(), 4);
+
+        let late = ttf(vec![(12., 1.), (40., 1.)]);
+        let linked = f.link(&late).unwrap();
+        // Departing at 4 arrives at 12 on `late`.
+        assert_eq!(linked.domain(), (4., 20.));
+        assert_eq!(linked.travel_time_at(4.), Some(9.));
+        assert_eq!(f.link(&ttf(vec![(50., 1.), (60., 1.)])), None);
+    }
+
+    #[test]
+    fn test_merge() {
+        let f = ttf(vec![(0., 10.), (10., 5.), (20., 10.)]);
+        let g = ttf(vec![(0., 6.), (20., 8.)]);
+        let merged = f.merge(&g).unwrap();
+        for i in 0..=20 {
+            let t = f64::from(i);
+            let expected = f
+                .travel_time_at(t)
+                .unwrap()
+                .min(g.travel_time_at(t).unwrap());
+            assert!((merged.travel_time_at(t).unwrap() - expected).abs() < 1e-12);
+        }
+        assert_eq!(f.merge(&ttf(vec![(25., 1.), (30., 1.)])), None);
+        assert_eq!(f.merge(&ttf(vec![(20., 1.), (30., 1.)])), None);
+    }
+
+    #[test]
+    fn test_merge_partial_overlap() {
+        let f = ttf(vec![(0., 10.), (10., 5.), (20., 10.)]);
+        let later = ttf(vec![(15., 1.), (30., 1.)]);
+        let merged = f.merge(&later).unwrap();
+        assert_eq!(merged.domain(), (15., 20.));
+        for &t in &[15., 17., 20.] {
+            assert_eq!(merged.travel_time_at(t), Some(1.));
+        }
+        assert_eq!(merged.travel_time_at(10.), None);
+        // The result satisfies the FIFO property.
+        assert!(TravelTimeFunction::new(merged.travel_time().clone()).is_some());
+        assert_eq!(later.merge(&f), Some(merged));
+    }
+
+    #[test]
+    fn test_simplify() {
+        let points = (0..=100)
+            .map(|i| {
+                let t = f64::from(i);
+                (t, 10. + 3. * (t / 10.).sin())
+            })
+            .collect();
+        let f = ttf(points);
+        let simplified = f.simplify(0.1);
+        assert!(simplified.travel_time().coordinates.len() < 30);
+        assert!(TravelTimeFunction::new(simplified.travel_time().clone()).is_some());
+        for c in &f.travel_time().coordinates {
+            assert!((simplified.travel_time_at(c.x).unwrap() - c.y).abs() <= 0.1);
+        }
+        assert_eq!(f.simplify(0.), f);
+    }
+}
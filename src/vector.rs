@@ -0,0 +1,323 @@
+// Copyright 2019 Matthieu Felix
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Piecewise linear functions from a scalar to a vector, such as trajectories over time.
+
+use std::array;
+
+use crate::{
+    bogus_compare, points_of_inflection_iter, y_at_x, Coord, CoordFloat, Error, Line, LineString,
+    PiecewiseLinearFunction,
+};
+
+/// A continuous piecewise linear function mapping a scalar `x` to a vector of `N` values.
+///
+/// All components share the same points of inflection. The points are sorted by strictly
+/// increasing, finite `x` value, and there are at least two of them.
+///
+/// ## Example
+///
+/// ```
+/// use piecewise_linear::PiecewiseLinearVectorFunction;
+/// // A trajectory in the plane, over time.
+/// let path = PiecewiseLinearVectorFunction::new(vec![
+///     (0., [0., 0.]),
+///     (1., [3., 4.]),
+///     (3., [3., 0.]),
+/// ])
+/// .unwrap();
+/// assert_eq!(path.value_at(2.), Some([3., 2.]));
+/// assert_eq!(path.arc_length(), 9.);
+/// ```
+#[derive(PartialEq, Clone, Debug)]
+pub struct PiecewiseLinearVectorFunction<T: CoordFloat, const N: usize> {
+    points: Vec<(T, [T; N])>,
+}
+
+impl<T: CoordFloat, const N: usize> PiecewiseLinearVectorFunction<T, N> {
+    /// Creates a new function from its points of inflection.
+    ///
+    /// Returns `Error::TooFewPoints` if there are fewer than two points, `Error::UnsortedPoints`
+    /// if their `x` values are not strictly increasing, and `Error::UnboundedDomain` if any of
+    /// them is infinite.
+    pub fn new(points: Vec<(T, [T; N])>) -> Result<Self, Error> {
+        if points.len() < 2 {
+            Err(Error::TooFewPoints)
+        } else if !points.windows(2).all(|w| w[0].0 < w[1].0) {
+            Err(Error::UnsortedPoints)
+        } else if !points.iter().all(|p| p.0.is_finite()) {
+            Err(Error::UnboundedDomain)
+        } else {
+            Ok(PiecewiseLinearVectorFunction { points })
+        }
+    }
+
+    /// Creates a function from one function per component, with the union of their points of
+    /// inflection.
+    ///
+    /// Returns `Error::DomainMismatch` if the functions do not all have the same domain, and
+    /// `Error::UnboundedDomain` if their domain is infinite.
+    pub fn from_components(components: &[PiecewiseLinearFunction<T>; N]) -> Result<Self, Error> {
+        if !components.iter().all(|f| f.is_bounded()) {
+            return Err(Error::UnboundedDomain);
+        }
+        let points = points_of_inflection_iter(components)
+            .ok_or(Error::DomainMismatch)?
+            .map(|(x, values)| (x, array::from_fn(|i| values[i])))
+            .collect();
+        Ok(PiecewiseLinearVectorFunction { points })
+    }
+
+    /// Returns the points of inflection of the function.
+    pub fn points(&self) -> &[(T, [T; N])] {
+        &self.points
+    }
+
+    /// Returns the function's domain, represented as its min and max.
+    pub fn domain(&self) -> (T, T) {
+        // This unwrap is guaranteed to succeed as there are at least two points.
+        (self.points[0].0, self.points.last().unwrap().0)
+    }
+
+    /// Returns the `i`-th component of the function, with the same points of inflection.
+    ///
+    /// Panics if `i >= N`.
+    pub fn component(&self, i: usize) -> PiecewiseLinearFunction<T> {
+        PiecewiseLinearFunction {
+            coordinates: self
+                .points
+                .iter()
+                .map(|(x, values)| Coord {
+                    x: *x,
+                    y: values[i],
+                })
+                .collect(),
+        }
+    }
+
+    /// Computes the value f(x), with the same result for each component as `y_at_x()` on the
+    /// function returned by `component()`.
+    ///
+    /// Returns `None` if `x` is outside the domain of f.
+    pub fn value_at(&self, x: T) -> Option<[T; N]> {
+        let idx = match self
+            .points
+            .binary_search_by(|point| bogus_compare(&point.0, &x))
+        {
+            Ok(0) => 1,
+            Ok(idx) => idx,
+            Err(idx) if idx == 0 || idx == self.points.len() => return None,
+            Err(idx) => idx,
+        };
+        let ((x0, start), (x1, end)) = (&self.points[idx - 1], &self.points[idx]);
+        Some(array::from_fn(|i| {
+            y_at_x(&Line::new((*x0, start[i]), (*x1, end[i])), x)
+        }))
+    }
+
+    /// Sums this function with another one, component by component.
+    ///
+    /// Both functions must have the same domain; returns `None` otherwise.
+    pub fn add(&self, other: &PiecewiseLinearVectorFunction<T, N>) -> Option<Self> {
+        if self.domain() != other.domain() {
+            return None;
+        }
+        let mut xs: Vec<T> = self
+            .points
+            .iter()
+            .chain(other.points.iter())
+            .map(|p| p.0)
+            .collect();
+        xs.sort_by(bogus_compare);
+        xs.dedup();
+        let points = xs
+            .into_iter()
+            .map(|x| {
+                // These unwraps are guaranteed to succeed as `x` is in the common domain.
+                let (a, b) = (self.value_at(x).unwrap(), other.value_at(x).unwrap());
+                (x, array::from_fn(|i| a[i] + b[i]))
+            })
+            .collect();
+        Some(PiecewiseLinearVectorFunction { points })
+    }
+
+    /// Returns the function multiplied by `factor`.
+    pub fn scale(&self, factor: T) -> Self {
+        PiecewiseLinearVectorFunction {
+            points: self
+                .points
+                .iter()
+                .map(|(x, values)| (*x, values.map(|v| v * factor)))
+                .collect(),
+        }
+    }
+
+    /// Returns the Euclidean length of the curve traced by the function.
+    pub fn arc_length(&self) -> T {
+        self.points
+            .windows(2)
+            .map(|w| {
+                w[0].1
+                    .iter()
+                    .zip(w[1].1.iter())
+                    .fold(T::zero(), |total, (a, b)| total + (*b - *a) * (*b - *a))
+                    .sqrt()
+            })
+            .fold(T::zero(), |total, length| total + length)
+    }
+}
+
+impl<T: CoordFloat> PiecewiseLinearVectorFunction<T, 2> {
+    /// Creates a time-parametrized path, where `times[i]` is the time at which the path goes
+    /// through the `i`-th point of `line`.
+    ///
+    /// Returns `Error::DomainMismatch` if `times` and `line` have different lengths, and the
+    /// errors of `new()` otherwise.
+    pub fn from_line_string(line: &LineString<T>, times: &[T]) -> Result<Self, Error> {
+        if line.0.len() != times.len() {
+            return Err(Error::DomainMismatch);
+        }
+        Self::new(
+            times
+                .iter()
+                .zip(line.0.iter())
+                .map(|(t, c)| (*t, [c.x, c.y]))
+                .collect(),
+        )
+    }
+
+    /// Returns the path traced by the function, without its time parametrization.
+    pub fn to_line_string(&self) -> LineString<T> {
+        LineString(
+            self.points
+                .iter()
+                .map(|(_, [x, y])| Coord { x: *x, y: *y })
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use super::*;
+
+    fn get_trajectory() -> PiecewiseLinearVectorFunction<f64, 3> {
+        PiecewiseLinearVectorFunction::new(vec![
+            (0., [0., 0., 100.]),
+            (10., [1., 2., 120.]),
+            (20., [1., 4., 110.]),
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn test_new() {
+        assert_eq!(
+            PiecewiseLinearVectorFunction::new(vec![(0., [1.])]),
+            Err(Error::TooFewPoints)
+        );
+        assert_eq!(
+            PiecewiseLinearVectorFunction::new(vec![(1., [1.]), (0., [1.])]),
+            Err(Error::UnsortedPoints)
+        );
+        assert_eq!(
+            PiecewiseLinearVectorFunction::new(vec![(0., [1.]), (f64::INFINITY, [1.])]),
+            Err(Error::UnboundedDomain)
+        );
+    }
+
+    #[test]
+    fn test_evaluation_and_components() {
+        let f = get_trajectory();
+        assert_eq!(f.domain(), (0., 20.));
+        assert_eq!(f.value_at(5.), Some([0.5, 1., 110.]));
+        assert_eq!(f.value_at(20.), Some([1., 4., 110.]));
+        assert_eq!(f.value_at(-1.), None);
+        assert_eq!(f.value_at(21.), None);
+        let altitude = f.component(2);
+        assert_eq!(
+            altitude,
+            PiecewiseLinearFunction::try_from(vec![(0., 100.), (10., 120.), (20., 110.)]).unwrap()
+        );
+        for &x in &[0., 3., 10., 17.5, 20.] {
+            assert_eq!(f.value_at(x).unwrap()[2], altitude.y_at_x(x).unwrap());
+        }
+        let components = [f.component(0), f.component(1), f.component(2)];
+        assert_eq!(
+            PiecewiseLinearVectorFunction::from_components(&components),
+            Ok(f)
+        );
+    }
+
+    #[test]
+    fn test_from_components() {
+        let a = PiecewiseLinearFunction::try_from(vec![(0., 0.), (1., 1.), (2., 0.)]).unwrap();
+        let b = PiecewiseLinearFunction::try_from(vec![(0., 0.), (1.5, 3.), (2., 3.)]).unwrap();
+        assert_eq!(
+            PiecewiseLinearVectorFunction::from_components(&[a.clone(), b]).unwrap(),
+            PiecewiseLinearVectorFunction::new(vec![
+                (0., [0., 0.]),
+                (1., [1., 2.]),
+                (1.5, [0.5, 3.]),
+                (2., [0., 3.]),
+            ])
+            .unwrap()
+        );
+        let short = PiecewiseLinearFunction::try_from(vec![(0., 0.), (1., 1.)]).unwrap();
+        assert_eq!(
+            PiecewiseLinearVectorFunction::from_components(&[a, short]),
+            Err(Error::DomainMismatch)
+        );
+    }
+
+    #[test]
+    fn test_operations() {
+        let f = get_trajectory();
+        let g = PiecewiseLinearVectorFunction::new(vec![(0., [1., 1., 1.]), (20., [1., 1., 21.])])
+            .unwrap();
+        let sum = f.add(&g).unwrap();
+        assert_eq!(sum.points().len(), 3);
+        assert_eq!(sum.value_at(10.), Some([2., 3., 131.]));
+        assert_eq!(f.add(&f).unwrap(), f.scale(2.));
+        let short =
+            PiecewiseLinearVectorFunction::new(vec![(0., [0., 0., 0.]), (1., [0., 0., 0.])])
+                .unwrap();
+        assert_eq!(f.add(&short), None);
+
+        let square = PiecewiseLinearVectorFunction::new(vec![
+            (0., [0., 0.]),
+            (1., [1., 0.]),
+            (2., [1., 1.]),
+            (3., [0., 1.]),
+            (4., [0., 0.]),
+        ])
+        .unwrap();
+        assert_eq!(square.arc_length(), 4.);
+        assert_eq!(square.scale(3.).arc_length(), 12.);
+    }
+
+    #[test]
+    fn test_line_strings() {
+        let line = LineString::from(vec![(0., 0.), (3., 4.), (3., 0.)]);
+        let path = PiecewiseLinearVectorFunction::from_line_string(&line, &[0., 5., 9.]).unwrap();
+        assert_eq!(path.value_at(2.5), Some([1.5, 2.]));
+        assert_eq!(path.to_line_string(), line);
+        assert_eq!(
+            PiecewiseLinearVectorFunction::from_line_string(&line, &[0., 1.]),
+            Err(Error::DomainMismatch)
+        );
+    }
+}